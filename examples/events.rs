@@ -16,7 +16,31 @@ pub fn print_events(mut events: EventReader<PickingEvent>) {
         match event {
             PickingEvent::Selection(e) => info!("A selection event happened: {:?}", e),
             PickingEvent::Hover(e) => info!("Egads! A hover event!? {:?}", e),
-            PickingEvent::Clicked(e) => info!("Gee Willikers, it's a click! {:?}", e),
+            PickingEvent::Pressed(e, pointer, button, hit) => {
+                info!(
+                    "Pressed {:?} with {:?} of {:?} at {}",
+                    e, button, pointer, hit.position
+                )
+            }
+            PickingEvent::Released(e, pointer, button, hit) => {
+                info!(
+                    "Released {:?} with {:?} of {:?} at {}",
+                    e, button, pointer, hit.position
+                )
+            }
+            PickingEvent::Clicked(e) => {
+                info!(
+                    "Gee Willikers, it's a {:?} click x{}! {:?} by {:?} at {}",
+                    e.button, e.count, e.entity, e.pointer, e.hit.position
+                )
+            }
+            PickingEvent::Drag(e) => info!("Whoa, a drag! {:?}", e),
+            PickingEvent::ClickedNothing(pointer, button, ray) => {
+                info!(
+                    "{:?} clicked nothing with {:?}, along {:?}",
+                    pointer, button, ray
+                )
+            }
         }
     }
 }
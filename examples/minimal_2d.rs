@@ -1,6 +1,7 @@
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 use bevy_mod_picking::{
-    DebugEventsPickingPlugin, DefaultPickingPlugins, PickableBundle, PickingCameraBundle,
+    DebugEventsPickingPlugin, DefaultPickingPlugins, PickableBundle, PickableSpriteBundle,
+    PickingCameraBundle,
 };
 
 fn main() {
@@ -27,6 +28,18 @@ fn setup(
         },
         PickableBundle::default(), // <- Makes the mesh pickable.
     ));
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::ORANGE,
+                custom_size: Some(Vec2::splat(96.)),
+                ..default()
+            },
+            transform: Transform::from_xyz(160., 0., 1.),
+            ..default()
+        },
+        PickableSpriteBundle::default(), // <- Makes the sprite pickable.
+    ));
     // camera
     commands.spawn(
         (Camera2dBundle::default(), PickingCameraBundle::default()), // <- Sets the camera to use for picking.
@@ -0,0 +1,116 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::camera::Viewport,
+    window::{WindowId, WindowResized},
+};
+use bevy_mod_picking::{
+    DebugEventsPickingPlugin, DefaultPickingPlugins, PickableBundle, PickingCameraBundle,
+};
+
+/// Two picking cameras share the window, each with its own viewport. The pointer only picks through
+/// the camera whose viewport it is over.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(DefaultPickingPlugins) // <- Adds picking, interaction, and highlighting
+        .add_plugin(DebugEventsPickingPlugin) // <- Adds debug event logging (optional)
+        .add_startup_system(setup)
+        .add_system(set_camera_viewports)
+        .run();
+}
+
+/// set up a simple 3D scene
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // plane
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Plane { size: 5.0 })),
+            material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+            ..Default::default()
+        },
+        PickableBundle::default(),
+    ));
+    // cube
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+            material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+            transform: Transform::from_xyz(0.0, 0.5, 0.0),
+            ..Default::default()
+        },
+        PickableBundle::default(),
+    ));
+    // light
+    commands.spawn(PointLightBundle {
+        point_light: PointLight {
+            intensity: 1500.0,
+            shadows_enabled: true,
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..Default::default()
+    });
+    // left camera
+    commands.spawn((
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..Default::default()
+        },
+        PickingCameraBundle::default(),
+        LeftCamera,
+    ));
+    // right camera
+    commands.spawn((
+        Camera3dBundle {
+            transform: Transform::from_xyz(5.0, 2.5, -2.0).looking_at(Vec3::ZERO, Vec3::Y),
+            camera: Camera {
+                priority: 1,
+                ..Default::default()
+            },
+            camera_3d: Camera3d {
+                // The left camera already cleared the window
+                clear_color: ClearColorConfig::None,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        PickingCameraBundle::default(),
+        RightCamera,
+    ));
+}
+
+#[derive(Component)]
+struct LeftCamera;
+
+#[derive(Component)]
+struct RightCamera;
+
+/// Gives each camera half of the window whenever the window is resized.
+fn set_camera_viewports(
+    windows: Res<Windows>,
+    mut resize_events: EventReader<WindowResized>,
+    mut left_camera: Query<&mut Camera, (With<LeftCamera>, Without<RightCamera>)>,
+    mut right_camera: Query<&mut Camera, With<RightCamera>>,
+) {
+    for resize_event in resize_events.iter() {
+        if resize_event.id == WindowId::primary() {
+            let window = windows.primary();
+            let half_size = UVec2::new(window.physical_width() / 2, window.physical_height());
+            left_camera.single_mut().viewport = Some(Viewport {
+                physical_position: UVec2::ZERO,
+                physical_size: half_size,
+                ..Default::default()
+            });
+            right_camera.single_mut().viewport = Some(Viewport {
+                physical_position: UVec2::new(half_size.x, 0),
+                physical_size: half_size,
+                ..Default::default()
+            });
+        }
+    }
+}
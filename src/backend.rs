@@ -0,0 +1,156 @@
+use crate::{
+    mouse::{pointer_in_viewport, PointerRay},
+    simple_criteria, PickingCamera, PickingPluginsState, PickingRaycastSet, PickingSystem,
+    PointerHit, PointerId, Pointers,
+};
+use bevy::{prelude::*, render::camera::RenderTarget};
+use std::marker::PhantomData;
+
+/// An entity under a pointer, found by a [PickingBackend].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendHit {
+    /// The render target of the camera the hit was made through, if it was made through one. Hits
+    /// on an image shown by a [PickingPortal](crate::PickingPortal) are on top of the portal.
+    pub target: Option<RenderTarget>,
+    /// Among hits on the same target, hits with a higher order are drawn on top, e.g. hits through
+    /// cameras with a higher priority.
+    pub order: isize,
+    /// How far into the scene the hit is. Within an order, hits with a smaller depth are on top.
+    pub depth: f32,
+    pub hit: PointerHit,
+}
+
+/// The hits every [PickingBackend] found this frame. Focus, and with it selection, highlighting
+/// and events, only looks at this resource, so it does not matter which backend found a hit.
+#[derive(Debug, Default, Resource)]
+pub struct PickingHits {
+    hits: Vec<BackendHit>,
+}
+
+impl PickingHits {
+    pub fn push(
+        &mut self,
+        target: Option<RenderTarget>,
+        order: isize,
+        depth: f32,
+        hit: PointerHit,
+    ) {
+        self.hits.push(BackendHit {
+            target,
+            order,
+            depth,
+            hit,
+        });
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &BackendHit> {
+        self.hits.iter()
+    }
+    /// The hits of `pointer`, in no particular order.
+    pub fn iter_pointer(&self, pointer: PointerId) -> impl Iterator<Item = &BackendHit> {
+        self.hits
+            .iter()
+            .filter(move |hit| hit.hit.pointer == pointer)
+    }
+    pub fn clear(&mut self) {
+        self.hits.clear();
+    }
+}
+
+/// Empties [PickingHits] before the backends run.
+pub fn clear_picking_hits(mut hits: ResMut<PickingHits>) {
+    hits.clear();
+}
+
+/// A way of finding what is under each pointer, such as raycasting against meshes. Backends push
+/// what they find into [PickingHits], and are added with a [PickingBackendPlugin].
+pub trait PickingBackend: Send + Sync + 'static {
+    /// The systems that fill [PickingHits]. They run in [CoreStage::First] after the pointers are
+    /// updated, and are labelled [PickingSystem::Backend].
+    fn systems() -> SystemSet;
+}
+
+/// Adds the [PickingBackend] `B`, which runs while picking is enabled.
+pub struct PickingBackendPlugin<B: PickingBackend>(PhantomData<B>);
+
+impl<B: PickingBackend> Default for PickingBackendPlugin<B> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<B: PickingBackend> Plugin for PickingBackendPlugin<B> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PickingHits>().add_system_set_to_stage(
+            CoreStage::First,
+            B::systems()
+                .with_run_criteria(|state: Res<PickingPluginsState>| {
+                    simple_criteria(state.enable_picking)
+                })
+                .label(PickingSystem::Backend)
+                .after(PickingSystem::UpdatePointers),
+        );
+    }
+}
+
+/// Raycasts against [PickableMesh](crate::PickableMesh)es with `bevy_mod_raycast`, from every
+/// [PickingCamera].
+pub struct RaycastBackend;
+
+impl PickingBackend for RaycastBackend {
+    fn systems() -> SystemSet {
+        SystemSet::new()
+            .with_system(
+                bevy_mod_raycast::build_rays::<PickingRaycastSet>
+                    .label(PickingSystem::BuildRays)
+                    .before(PickingSystem::UpdateRaycast),
+            )
+            .with_system(
+                bevy_mod_raycast::update_raycast::<PickingRaycastSet>
+                    .label(PickingSystem::UpdateRaycast)
+                    .before(PickingSystem::UpdateIntersections),
+            )
+            .with_system(
+                bevy_mod_raycast::update_intersections::<PickingRaycastSet>
+                    .label(PickingSystem::UpdateIntersections),
+            )
+            .with_system(raycast_hits.after(PickingSystem::UpdateIntersections))
+    }
+}
+
+/// Pushes the intersections of every [PickingCamera] into [PickingHits].
+pub fn raycast_hits(
+    pointers: Res<Pointers>,
+    mut hits: ResMut<PickingHits>,
+    pick_source_query: Query<(&PickingCamera, Option<&PointerRay>, Option<&Camera>)>,
+    camera_query: Query<&Camera>,
+) {
+    for (pick_source, pointer_ray, source_camera) in pick_source_query.iter() {
+        // Sources driven by the mouse are the cameras themselves; every other pointer has its own
+        // ray per camera. Rays only count while their pointer is inside the camera's viewport, so
+        // cameras sharing a window don't pick through each other.
+        let (pointer, camera) = match pointer_ray {
+            Some(ray) => (ray.pointer, camera_query.get(ray.camera).ok()),
+            None => (PointerId::Mouse, source_camera),
+        };
+        if let Some(camera) = camera {
+            if !camera.is_active || !pointer_in_viewport(&pointers, pointer, camera) {
+                continue;
+            }
+        }
+        let target = camera.map(|camera| camera.target.clone());
+        let priority = camera.map_or(0, |camera| camera.priority);
+        for (entity, intersection) in pick_source.intersections().iter() {
+            let hit = PointerHit {
+                pointer,
+                entity: *entity,
+                position: intersection.position(),
+                normal: intersection.normal(),
+                distance: intersection.distance(),
+                triangle: intersection
+                    .triangle()
+                    .map(|t| [t.v0.into(), t.v1.into(), t.v2.into()]),
+            };
+            hits.push(target.clone(), priority, intersection.distance(), hit);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::PickingEvent;
+use bevy::prelude::*;
+
+/// A [PickingEvent] delivered to one entity on the path from the entity it happened on up the
+/// [Parent] hierarchy. This lets the root of an imported scene handle events on its child meshes.
+#[derive(Debug, Clone)]
+pub struct BubbledEvent {
+    /// The entity the event happened on, e.g. the mesh that was clicked.
+    pub target: Entity,
+    /// The entity receiving this copy of the event: the target itself, or one of its ancestors.
+    pub listener: Entity,
+    pub event: PickingEvent,
+}
+
+/// Stops [PickingEvent]s bubbling past this entity. The entity itself still receives them as
+/// [BubbledEvent]s, but its ancestors do not.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct StopPropagation;
+
+/// Sends a [BubbledEvent] for every [PickingEvent] to its target and each of the target's
+/// ancestors, until the root or an entity with [StopPropagation] is reached.
+pub fn bubble_picking_events(
+    mut picking_events: EventReader<PickingEvent>,
+    mut bubbled_events: EventWriter<BubbledEvent>,
+    parents: Query<&Parent>,
+    stop_query: Query<(), With<StopPropagation>>,
+) {
+    for event in picking_events.iter() {
+        let target = match event.entity() {
+            Some(entity) => entity,
+            None => continue,
+        };
+        let mut listener = Some(target);
+        while let Some(current) = listener {
+            bubbled_events.send(BubbledEvent {
+                target,
+                listener: current,
+                event: event.clone(),
+            });
+            listener = match stop_query.contains(current) {
+                true => None,
+                false => parents.get(current).ok().map(|parent| parent.get()),
+            };
+        }
+    }
+}
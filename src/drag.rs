@@ -0,0 +1,194 @@
+use crate::{DragEvent, PickingEvent, PointerButton, PointerFocus, PointerId, Pointers};
+use bevy::{prelude::*, utils::HashMap};
+
+/// Configures when a press turns into a drag.
+#[derive(Debug, Clone, Resource)]
+pub struct DragSettings {
+    /// How far, in logical pixels, a pointer has to move while pressed before the press counts as
+    /// a drag.
+    pub threshold: f32,
+}
+
+impl Default for DragSettings {
+    fn default() -> Self {
+        Self { threshold: 4.0 }
+    }
+}
+
+/// A press that may turn, or has turned, into a drag.
+#[derive(Debug, Clone)]
+struct Drag {
+    dragged: Vec<Entity>,
+    start: Vec2,
+    last: Vec2,
+    started: bool,
+    target: Option<Entity>,
+}
+
+/// The entities each pointer button is dragging.
+#[derive(Debug, Default, Resource)]
+pub struct PointerDrags {
+    drags: HashMap<(PointerId, PointerButton), Drag>,
+    just_ended: HashMap<(PointerId, PointerButton), Vec<Entity>>,
+}
+
+impl PointerDrags {
+    /// The entities `button` of `pointer` is dragging, if the press has become a drag.
+    pub fn dragging(&self, pointer: PointerId, button: PointerButton) -> &[Entity] {
+        match self.drags.get(&(pointer, button)) {
+            Some(drag) if drag.started => drag.dragged.as_slice(),
+            _ => &[],
+        }
+    }
+    /// The entity `button` of `pointer` is dragging over, if any.
+    pub fn target(&self, pointer: PointerId, button: PointerButton) -> Option<Entity> {
+        self.drags
+            .get(&(pointer, button))
+            .filter(|drag| drag.started)
+            .and_then(|drag| drag.target)
+    }
+    /// The entities `button` of `pointer` stopped dragging since the last frame.
+    pub fn just_ended(&self, pointer: PointerId, button: PointerButton) -> &[Entity] {
+        self.just_ended
+            .get(&(pointer, button))
+            .map_or(&[], |e| e.as_slice())
+    }
+    pub fn is_dragging(&self, entity: Entity) -> bool {
+        self.drags
+            .values()
+            .any(|drag| drag.started && drag.dragged.contains(&entity))
+    }
+}
+
+/// Turns presses that moved further than [DragSettings::threshold] into drags, and sends
+/// [DragEvent]s for the dragged entities and the entities they are dragged over.
+pub fn mesh_drag_events(
+    pointers: Res<Pointers>,
+    focus: Res<PointerFocus>,
+    settings: Res<DragSettings>,
+    mut drags: ResMut<PointerDrags>,
+    mut picking_events: EventWriter<PickingEvent>,
+) {
+    let mut send = |event: DragEvent| picking_events.send(PickingEvent::Drag(event));
+    let drags = drags.as_mut();
+    drags.just_ended.clear();
+
+    for (pointer, state) in pointers.iter() {
+        let position = match state.location() {
+            Some(location) => location.position,
+            None => continue,
+        };
+        for button in state.iter_just_pressed() {
+            let dragged = focus.pressed(pointer, button);
+            if dragged.is_empty() {
+                continue;
+            }
+            let drag = Drag {
+                dragged: dragged.to_vec(),
+                start: position,
+                last: position,
+                started: false,
+                target: None,
+            };
+            drags.drags.insert((pointer, button), drag);
+        }
+    }
+
+    drags.drags.retain(|(pointer, button), drag| {
+        let (pointer, button) = (*pointer, *button);
+        let state = pointers.get(pointer);
+        let position = state
+            .and_then(|state| state.location())
+            .map_or(drag.last, |location| location.position);
+
+        if !drag.started && position.distance(drag.start) >= settings.threshold {
+            drag.started = true;
+            for dragged in drag.dragged.iter() {
+                send(DragEvent::DragStart {
+                    dragged: *dragged,
+                    pointer,
+                    button,
+                });
+            }
+        }
+
+        if drag.started {
+            // The dragged entities are usually under the pointer, so look past them for the
+            // entity they are being dragged over.
+            let hit = focus
+                .hits(pointer)
+                .iter()
+                .find(|hit| !drag.dragged.contains(&hit.entity));
+            let delta = position - drag.last;
+            if delta != Vec2::ZERO {
+                for dragged in drag.dragged.iter() {
+                    send(DragEvent::Drag {
+                        dragged: *dragged,
+                        pointer,
+                        button,
+                        delta,
+                        hit: hit.map(|hit| hit.position),
+                    });
+                }
+            }
+
+            let target = hit.map(|hit| hit.entity);
+            if target != drag.target {
+                if let Some(previous) = drag.target {
+                    for dragged in drag.dragged.iter() {
+                        send(DragEvent::DragLeave {
+                            dragged: *dragged,
+                            target: previous,
+                            pointer,
+                        });
+                    }
+                }
+                if let Some(target) = target {
+                    for dragged in drag.dragged.iter() {
+                        send(DragEvent::DragEnter {
+                            dragged: *dragged,
+                            target,
+                            pointer,
+                        });
+                    }
+                }
+                drag.target = target;
+            }
+            if let Some(target) = target {
+                for dragged in drag.dragged.iter() {
+                    send(DragEvent::DragOver {
+                        dragged: *dragged,
+                        target,
+                        pointer,
+                    });
+                }
+            }
+        }
+        drag.last = position;
+
+        let released = !state.is_some_and(|state| state.pressed(button));
+        if !released {
+            return true;
+        }
+        if drag.started {
+            for dragged in drag.dragged.iter() {
+                if let Some(target) = drag.target {
+                    send(DragEvent::Drop {
+                        dragged: *dragged,
+                        target,
+                        pointer,
+                    });
+                }
+                send(DragEvent::DragEnd {
+                    dragged: *dragged,
+                    pointer,
+                    button,
+                });
+            }
+            drags
+                .just_ended
+                .insert((pointer, button), std::mem::take(&mut drag.dragged));
+        }
+        false
+    });
+}
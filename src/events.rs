@@ -1,74 +1,391 @@
-use crate::{Hover, PickableMesh, Selection};
-use bevy::prelude::*;
+use crate::{
+    Hover, PausedForBlockers, PickingCamera, PointerButton, PointerDrags, PointerFocus, PointerHit,
+    PointerId, Pointers, Selection, SelectionHits, SelectionSet,
+};
+use bevy::{ecs::system::SystemState, prelude::*, utils::HashMap};
+use std::time::Duration;
 
-/// An event that triggers when the selection state of a [Selection] enabled [PickableMesh] changes.
-#[derive(Debug)]
+/// An event that triggers when the selection state of a [Selection] enabled entity changes.
+/// Carries the hit of the pointer that clicked the entity, if the change came from a click.
+#[derive(Debug, Clone)]
 pub enum SelectionEvent {
-    JustSelected(Entity),
-    JustDeselected(Entity),
+    JustSelected(Entity, Option<PointerHit>),
+    JustDeselected(Entity, Option<PointerHit>),
 }
 
-/// An event that triggers when the hover state of a [Hover] enabled [PickableMesh] changes.
-#[derive(Debug)]
+/// An event that triggers when a pointer starts or stops hovering a [Hover] enabled entity.
+/// Carries where the pointer hit the entity; for `JustLeft` this is the last hit before the
+/// pointer left.
+#[derive(Debug, Clone)]
 pub enum HoverEvent {
-    JustEntered(Entity),
-    JustLeft(Entity),
+    JustEntered(Entity, PointerId, PointerHit),
+    JustLeft(Entity, PointerId, PointerHit),
+}
+
+/// An event that triggers while a pointer drags the pickable entities it pressed. A press only
+/// becomes a drag once the pointer moves further than
+/// [DragSettings::threshold](crate::DragSettings::threshold).
+#[derive(Debug, Clone)]
+pub enum DragEvent {
+    /// The pointer started dragging `dragged`.
+    DragStart {
+        dragged: Entity,
+        pointer: PointerId,
+        button: PointerButton,
+    },
+    /// The pointer moved by `delta` logical pixels while dragging `dragged`. `hit` is the world
+    /// position under the pointer, looking past the dragged entities.
+    Drag {
+        dragged: Entity,
+        pointer: PointerId,
+        button: PointerButton,
+        delta: Vec2,
+        hit: Option<Vec3>,
+    },
+    /// The pointer released `dragged`.
+    DragEnd {
+        dragged: Entity,
+        pointer: PointerId,
+        button: PointerButton,
+    },
+    /// `dragged` was dragged onto `target`.
+    DragEnter {
+        dragged: Entity,
+        target: Entity,
+        pointer: PointerId,
+    },
+    /// `dragged` is over `target`. Sent every frame until it leaves or is dropped.
+    DragOver {
+        dragged: Entity,
+        target: Entity,
+        pointer: PointerId,
+    },
+    /// `dragged` was dragged off `target`.
+    DragLeave {
+        dragged: Entity,
+        target: Entity,
+        pointer: PointerId,
+    },
+    /// `dragged` was released over `target`. Sent before the [DragEvent::DragEnd] of `dragged`.
+    Drop {
+        dragged: Entity,
+        target: Entity,
+        pointer: PointerId,
+    },
+}
+
+/// A pointer button was pressed and then released on the entity, without dragging in between.
+#[derive(Debug, Clone, Copy)]
+pub struct ClickEvent {
+    pub entity: Entity,
+    pub pointer: PointerId,
+    pub button: PointerButton,
+    /// How many clicks in a row this is: 1 for a single click, 2 for a double click, and so on.
+    /// Clicking another entity in between starts counting again.
+    pub count: u32,
+    /// Where the pointer hit the entity when it was released.
+    pub hit: PointerHit,
+}
+
+/// Configures when consecutive clicks count as a double or multi-click.
+#[derive(Debug, Clone, Resource)]
+pub struct ClickSettings {
+    /// The longest time between two clicks on the same entity for them to count as consecutive.
+    pub multi_click_time: Duration,
+    /// How far, in logical pixels, the pointer may move between two clicks for them to count as
+    /// consecutive.
+    pub multi_click_distance: f32,
+}
+
+impl Default for ClickSettings {
+    fn default() -> Self {
+        Self {
+            multi_click_time: Duration::from_millis(500),
+            multi_click_distance: 8.0,
+        }
+    }
+}
+
+impl ClickSettings {
+    /// The count of a click on `entity` at `position`, following `last`, the unexpired last click
+    /// of the same pointer button.
+    fn click_count(&self, last: Option<&LastClick>, entity: Entity, position: Vec2) -> u32 {
+        match last {
+            Some(last)
+                if last.entity == entity
+                    && last.position.distance(position) <= self.multi_click_distance =>
+            {
+                last.count + 1
+            }
+            _ => 1,
+        }
+    }
+}
+
+/// The last click of a pointer button.
+#[derive(Debug, Clone, Copy)]
+pub struct LastClick {
+    /// The topmost entity that was clicked.
+    entity: Entity,
+    time: Duration,
+    position: Vec2,
+    count: u32,
 }
 
 /// An event that wraps selection and hover events
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum PickingEvent {
     Selection(SelectionEvent),
     Hover(HoverEvent),
-    Clicked(Entity),
+    /// A pointer button was pressed while hovering the entity.
+    Pressed(Entity, PointerId, PointerButton, PointerHit),
+    /// A pointer button was released while hovering the entity.
+    Released(Entity, PointerId, PointerButton, PointerHit),
+    Clicked(ClickEvent),
+    Drag(DragEvent),
+    /// A pointer button was pressed over empty space in a picking camera's view. The ray is cast
+    /// from the topmost camera under the pointer, for placing things or moving the camera.
+    ClickedNothing(PointerId, PointerButton, Ray),
+}
+
+impl PickingEvent {
+    /// The entity the event happened on. For drags, this is the dragged entity, or the entity it is
+    /// dragged over for [DragEvent::DragEnter], [DragEvent::DragOver], [DragEvent::DragLeave] and
+    /// [DragEvent::Drop]. `None` for [PickingEvent::ClickedNothing].
+    pub fn entity(&self) -> Option<Entity> {
+        let entity = match self {
+            PickingEvent::Selection(SelectionEvent::JustSelected(entity, _))
+            | PickingEvent::Selection(SelectionEvent::JustDeselected(entity, _))
+            | PickingEvent::Hover(HoverEvent::JustEntered(entity, _, _))
+            | PickingEvent::Hover(HoverEvent::JustLeft(entity, _, _))
+            | PickingEvent::Pressed(entity, _, _, _)
+            | PickingEvent::Released(entity, _, _, _)
+            | PickingEvent::Clicked(ClickEvent { entity, .. }) => *entity,
+            PickingEvent::Drag(event) => match event {
+                DragEvent::DragStart { dragged, .. }
+                | DragEvent::Drag { dragged, .. }
+                | DragEvent::DragEnd { dragged, .. } => *dragged,
+                DragEvent::DragEnter { target, .. }
+                | DragEvent::DragOver { target, .. }
+                | DragEvent::DragLeave { target, .. }
+                | DragEvent::Drop { target, .. } => *target,
+            },
+            PickingEvent::ClickedNothing(..) => return None,
+        };
+        Some(entity)
+    }
 }
 
+/// The hovered entities [HoverEvent::JustEntered] was sent for, and [HoverEvent::JustLeft] not yet,
+/// with their last hit, for each pointer. Shared by [mesh_events_system] and
+/// [removed_pickable_events], so each entity that is entered is left exactly once.
+#[derive(Debug, Default, Resource)]
+pub struct EnteredHovers(HashMap<PointerId, Vec<PointerHit>>);
+
 /// Looks for changes in selection or hover state, and sends the appropriate events
-#[allow(clippy::type_complexity)]
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn mesh_events_system(
-    mouse_button_input: Res<Input<MouseButton>>,
-    touches_input: Res<Touches>,
+    pointers: Res<Pointers>,
+    focus: Res<PointerFocus>,
+    drags: Res<PointerDrags>,
+    selection_hits: Res<SelectionHits>,
+    time: Res<Time>,
+    click_settings: Res<ClickSettings>,
+    mut previous_hovered: ResMut<EnteredHovers>,
+    mut previous_pressed: Local<HashMap<(PointerId, PointerButton), Vec<Entity>>>,
+    mut last_clicks: Local<HashMap<(PointerId, PointerButton), LastClick>>,
     mut picking_events: EventWriter<PickingEvent>,
-    hover_query: Query<
-        (Entity, &Hover, ChangeTrackers<Hover>),
-        (Changed<Hover>, With<PickableMesh>),
-    >,
-    selection_query: Query<
-        (Entity, &Selection, ChangeTrackers<Selection>),
-        (Changed<Selection>, With<PickableMesh>),
-    >,
-    click_query: Query<(Entity, &Hover)>,
+    hover_query: Query<(), With<Hover>>,
+    selection_query: Query<(Entity, &Selection, ChangeTrackers<Selection>), Changed<Selection>>,
 ) {
-    for (entity, hover, hover_change) in hover_query.iter() {
-        if hover_change.is_added() {
-            continue; // Avoid a false change detection when a component is added.
+    // The hits of the hovered entities that send events, for each pointer.
+    let (focus, hover_query) = (focus.as_ref(), &hover_query);
+    let hovered_hits = |pointer: PointerId| {
+        focus
+            .hovered(pointer)
+            .iter()
+            .filter(|e| hover_query.contains(**e))
+            .filter_map(move |e| focus.hit(pointer, *e))
+    };
+
+    for (pointer, previous) in previous_hovered.0.iter() {
+        let hovered = focus.hovered(*pointer);
+        for hit in previous.iter().filter(|hit| !hovered.contains(&hit.entity)) {
+            if hover_query.contains(hit.entity) {
+                picking_events.send(PickingEvent::Hover(HoverEvent::JustLeft(
+                    hit.entity, *pointer, *hit,
+                )));
+            }
         }
-        if hover.hovered() {
-            picking_events.send(PickingEvent::Hover(HoverEvent::JustEntered(entity)));
-        } else {
-            picking_events.send(PickingEvent::Hover(HoverEvent::JustLeft(entity)));
+    }
+    for (pointer, _) in focus.iter_hovered() {
+        let previous = previous_hovered.0.get(&pointer);
+        for hit in hovered_hits(pointer).filter(|hit| {
+            !previous.is_some_and(|p| p.iter().any(|previous| previous.entity == hit.entity))
+        }) {
+            picking_events.send(PickingEvent::Hover(HoverEvent::JustEntered(
+                hit.entity, pointer, *hit,
+            )));
         }
     }
+    previous_hovered.0 = focus
+        .iter_hovered()
+        .map(|(pointer, _)| (pointer, hovered_hits(pointer).copied().collect()))
+        .collect();
+
     for (entity, selection, selection_change) in selection_query.iter() {
-        if selection_change.is_added() {
+        if selection_change.is_added() && !selection.selected() {
             continue; // Avoid a false change detection when a component is added.
         }
+        let hit = selection_hits.get(entity).copied();
         if selection.selected() {
             picking_events.send(PickingEvent::Selection(SelectionEvent::JustSelected(
-                entity,
+                entity, hit,
             )));
         } else {
             picking_events.send(PickingEvent::Selection(SelectionEvent::JustDeselected(
-                entity,
+                entity, hit,
             )));
         }
     }
-    if mouse_button_input.just_pressed(MouseButton::Left)
-        || touches_input.iter_just_pressed().next().is_some()
+    let now = time.raw_elapsed();
+    last_clicks.retain(|_, last| now - last.time <= click_settings.multi_click_time);
+    for (pointer, state) in pointers.iter() {
+        let position = state.location().map(|location| location.position);
+        for button in state.iter_just_pressed() {
+            for hit in hovered_hits(pointer) {
+                picking_events.send(PickingEvent::Pressed(hit.entity, pointer, button, *hit));
+            }
+        }
+        for button in state.iter_just_released() {
+            for hit in hovered_hits(pointer) {
+                picking_events.send(PickingEvent::Released(hit.entity, pointer, button, *hit));
+            }
+            // A press that turned into a drag is not a click, wherever it is released.
+            if !drags.just_ended(pointer, button).is_empty() {
+                continue;
+            }
+            let pressed = previous_pressed.get(&(pointer, button));
+            let clicked: Vec<_> = hovered_hits(pointer)
+                .filter(|hit| pressed.is_some_and(|p| p.contains(&hit.entity)))
+                .collect();
+            let top = match clicked.first() {
+                Some(top) => top.entity,
+                None => continue,
+            };
+            // Everything under the pointer is clicked together, so it all shares one count.
+            let position = position.unwrap_or_default();
+            let count =
+                click_settings.click_count(last_clicks.get(&(pointer, button)), top, position);
+            last_clicks.insert(
+                (pointer, button),
+                LastClick {
+                    entity: top,
+                    time: now,
+                    position,
+                    count,
+                },
+            );
+            for hit in clicked {
+                picking_events.send(PickingEvent::Clicked(ClickEvent {
+                    entity: hit.entity,
+                    pointer,
+                    button,
+                    count,
+                    hit: *hit,
+                }));
+            }
+        }
+    }
+    // Presses are forgotten by the focus system on the frame they are released, so keep them
+    // around to match releases against.
+    *previous_pressed = focus
+        .iter_pressed()
+        .map(|(pointer, button, pressed)| ((pointer, button), pressed.to_vec()))
+        .collect();
+}
+
+/// Sends [HoverEvent::JustLeft] and [SelectionEvent::JustDeselected] for hovered and selected
+/// entities that were despawned, or lost their [Hover] or [Selection] component, and drops them
+/// from the [SelectionSet].
+///
+/// Rather than reading the removals, which are forgotten at the start of [CoreStage::Last], this
+/// checks which entities are still there. It runs at the end of [CoreStage::Last], after every
+/// other system and command of the frame, so an entity is caught the frame it goes away.
+#[allow(clippy::type_complexity)]
+pub fn removed_pickable_events(
+    world: &mut World,
+    state: &mut SystemState<(
+        ResMut<EnteredHovers>,
+        ResMut<SelectionSet>,
+        Query<(), With<Hover>>,
+        Query<(), With<Selection>>,
+        EventWriter<PickingEvent>,
+    )>,
+) {
+    let (mut entered, mut selection_set, hovers, selections, mut picking_events) =
+        state.get_mut(world);
+    for (pointer, hits) in entered.0.iter_mut() {
+        hits.retain(|hit| {
+            let left = !hovers.contains(hit.entity);
+            if left {
+                picking_events.send(PickingEvent::Hover(HoverEvent::JustLeft(
+                    hit.entity, *pointer, *hit,
+                )));
+            }
+            !left
+        });
+    }
+    let removed: Vec<Entity> = selection_set
+        .iter()
+        .filter(|entity| !selections.contains(*entity))
+        .collect();
+    for entity in removed {
+        selection_set.remove(entity);
+        picking_events.send(PickingEvent::Selection(SelectionEvent::JustDeselected(
+            entity, None,
+        )));
+    }
+}
+
+/// Sends [PickingEvent::ClickedNothing] for every pointer button pressed while the pointer hovers
+/// no pickable entity. As with deselecting on a click in empty space, nothing is sent while the UI
+/// or a [PickingBlocker](crate::PickingBlocker) is in the way.
+pub fn clicked_nothing_events(
+    paused: Option<Res<PausedForBlockers>>,
+    pointers: Res<Pointers>,
+    focus: Res<PointerFocus>,
+    cameras: Query<(&Camera, &GlobalTransform), With<PickingCamera>>,
+    node_query: Query<&Interaction, With<Node>>,
+    mut picking_events: EventWriter<PickingEvent>,
+) {
+    if paused.is_some_and(|paused| paused.0) {
+        return;
+    }
+    if node_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked)
     {
-        for (entity, hover) in click_query.iter() {
-            if hover.hovered() {
-                picking_events.send(PickingEvent::Clicked(entity));
+        return;
+    }
+    for (pointer, state) in pointers.iter() {
+        if !focus.hovered(pointer).is_empty() {
+            continue;
+        }
+        // The topmost camera under the pointer is the one the user sees.
+        let ray = cameras
+            .iter()
+            .filter(|(camera, _)| camera.is_active)
+            .filter_map(|(camera, transform)| {
+                Some((camera, transform, state.viewport_position(camera)?))
+            })
+            .max_by_key(|(camera, ..)| camera.priority)
+            .and_then(|(camera, transform, position)| {
+                camera.viewport_to_world(transform, position)
+            });
+        if let Some(ray) = ray {
+            for button in state.iter_just_pressed() {
+                picking_events.send(PickingEvent::ClickedNothing(pointer, button, ray));
             }
         }
     }
@@ -80,3 +397,113 @@ pub fn event_debug_system(mut events: EventReader<PickingEvent>) {
         info!("{:?}", event);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::update_selection_set;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<SelectionSet>()
+            .init_resource::<EnteredHovers>()
+            .add_event::<PickingEvent>()
+            .add_system_to_stage(CoreStage::First, update_selection_set)
+            .add_system_to_stage(CoreStage::Last, removed_pickable_events.at_end());
+        app
+    }
+
+    fn events(app: &App) -> Vec<PickingEvent> {
+        let events = app.world.resource::<Events<PickingEvent>>();
+        events.get_reader().iter(events).cloned().collect()
+    }
+
+    fn despawn_pickables(mut commands: Commands, pickables: Query<Entity, With<Selection>>) {
+        for entity in pickables.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    #[test]
+    fn clicking_another_entity_restarts_the_count() {
+        let settings = ClickSettings::default();
+        let (a, b) = (Entity::from_raw(0), Entity::from_raw(1));
+        let mut last = None;
+        let mut click = |entity| {
+            let count = settings.click_count(last.as_ref(), entity, Vec2::ZERO);
+            last = Some(LastClick {
+                entity,
+                time: Duration::ZERO,
+                position: Vec2::ZERO,
+                count,
+            });
+            count
+        };
+        assert_eq!(click(a), 1);
+        assert_eq!(click(a), 2);
+        assert_eq!(click(b), 1);
+        assert_eq!(click(a), 1);
+        assert_eq!(click(a), 2);
+    }
+
+    #[test]
+    fn moving_restarts_the_count() {
+        let settings = ClickSettings::default();
+        let entity = Entity::from_raw(0);
+        let last = LastClick {
+            entity,
+            time: Duration::ZERO,
+            position: Vec2::ZERO,
+            count: 1,
+        };
+        let near = Vec2::new(settings.multi_click_distance, 0.0);
+        assert_eq!(settings.click_count(Some(&last), entity, near), 2);
+        let far = Vec2::new(settings.multi_click_distance + 1.0, 0.0);
+        assert_eq!(settings.click_count(Some(&last), entity, far), 1);
+    }
+
+    #[test]
+    fn despawning_in_last_deselects() {
+        let mut app = app();
+        let mut selection = Selection::default();
+        selection.set_selected(true);
+        let entity = app.world.spawn(selection).id();
+        app.update();
+        assert_eq!(app.world.resource::<SelectionSet>().as_slice(), &[entity]);
+
+        app.add_system_to_stage(CoreStage::Last, despawn_pickables);
+        app.update();
+        assert!(app.world.resource::<SelectionSet>().is_empty());
+        assert!(events(&app).iter().any(|event| matches!(
+            event,
+            PickingEvent::Selection(SelectionEvent::JustDeselected(e, None)) if *e == entity
+        )));
+    }
+
+    #[test]
+    fn despawning_in_last_leaves_once() {
+        let mut app = app();
+        let entity = app
+            .world
+            .spawn((Selection::default(), Hover::default()))
+            .id();
+        let hit = PointerHit {
+            pointer: PointerId::Mouse,
+            entity,
+            position: Vec3::ZERO,
+            normal: Vec3::Z,
+            distance: 1.0,
+            triangle: None,
+        };
+        app.world.resource_mut::<EnteredHovers>().0 = [(PointerId::Mouse, vec![hit])].into();
+
+        app.add_system_to_stage(CoreStage::Last, despawn_pickables);
+        app.update();
+        app.update();
+        let left = events(&app)
+            .iter()
+            .filter(|event| matches!(event, PickingEvent::Hover(HoverEvent::JustLeft(..))))
+            .count();
+        assert_eq!(left, 1);
+    }
+}
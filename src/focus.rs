@@ -1,5 +1,8 @@
-use crate::{PausedForBlockers, PickableMesh, PickingCamera};
-use bevy::{prelude::*, ui::FocusPolicy};
+use crate::{
+    BackendHit, PausedForBlockers, PickingButtons, PickingHits, PickingPortal, PointerButton,
+    PointerId, Pointers, SelectionRoots,
+};
+use bevy::{prelude::*, render::camera::RenderTarget, ui::FocusPolicy, utils::HashMap};
 
 /// Tracks the current hover state to be used with change tracking in the events system.
 ///
@@ -18,9 +21,17 @@ impl Hover {
     }
 }
 
+/// Marks an entity whose [Interaction] and [Hover] are driven by picking, whichever
+/// [PickingBackend](crate::PickingBackend) hits it. Entities without it are never hovered or
+/// pressed, even when a backend hits them. [PickableBundle](crate::PickableBundle) and the sprite
+/// bundle add it.
+#[derive(Component, Debug, Copy, Clone, Reflect, Default)]
+#[reflect(Component)]
+pub struct Pickable;
+
 /// Marker component for entities that, whenever their [Interaction] component is anything other
-/// than `None`, will suspend highlighting and selecting [PickableMesh]s. Bevy UI [Node]s have this
-/// behavior by default.
+/// than `None`, will suspend highlighting and selecting [Pickable] entities. Bevy UI [Node]s have
+/// this behavior by default.
 #[derive(Component, Reflect, Default)]
 #[reflect(Component)]
 pub struct PickingBlocker;
@@ -36,7 +47,7 @@ pub fn pause_for_picking_blockers(
                 Option<&FocusPolicy>,
                 Entity,
             ),
-            With<PickableMesh>,
+            With<Pickable>,
         >,
         // UI nodes are picking blockers by default.
         Query<&Interaction, Or<(With<Node>, With<PickingBlocker>)>>,
@@ -61,12 +72,173 @@ pub fn pause_for_picking_blockers(
     }
 }
 
-#[allow(clippy::type_complexity)]
+/// Where a pointer's ray hit a pickable entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointerHit {
+    pub pointer: PointerId,
+    pub entity: Entity,
+    /// The world position of the hit.
+    pub position: Vec3,
+    /// The world normal of the surface that was hit.
+    pub normal: Vec3,
+    /// The distance from the ray origin to the hit.
+    pub distance: f32,
+    /// The world positions of the vertices of the triangle that was hit, if the hit was on a mesh.
+    pub triangle: Option<[Vec3; 3]>,
+}
+
+/// The entities each pointer is hovering, topmost first, and the entities each pointer button
+/// pressed and has not yet released.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct PointerFocus {
+    hits: HashMap<PointerId, Vec<PointerHit>>,
+    hovered: HashMap<PointerId, Vec<Entity>>,
+    pressed: HashMap<(PointerId, PointerButton), Vec<Entity>>,
+}
+
+impl PointerFocus {
+    /// Every pickable entity under `pointer`, topmost first, including those hidden behind an
+    /// entity with [FocusPolicy::Block].
+    pub fn hits(&self, pointer: PointerId) -> &[PointerHit] {
+        self.hits.get(&pointer).map_or(&[], |h| h.as_slice())
+    }
+    /// Where `pointer` hit `entity`, if it did.
+    pub fn hit(&self, pointer: PointerId, entity: Entity) -> Option<&PointerHit> {
+        self.hits(pointer).iter().find(|hit| hit.entity == entity)
+    }
+    /// The entities hovered by `pointer`, topmost first.
+    pub fn hovered(&self, pointer: PointerId) -> &[Entity] {
+        self.hovered.get(&pointer).map_or(&[], |e| e.as_slice())
+    }
+    /// The entities `button` of `pointer` pressed on and has not yet released.
+    pub fn pressed(&self, pointer: PointerId, button: PointerButton) -> &[Entity] {
+        self.pressed
+            .get(&(pointer, button))
+            .map_or(&[], |e| e.as_slice())
+    }
+    pub fn iter_hovered(&self) -> impl Iterator<Item = (PointerId, &[Entity])> {
+        self.hovered.iter().map(|(id, e)| (*id, e.as_slice()))
+    }
+    pub fn iter_pressed(&self) -> impl Iterator<Item = (PointerId, PointerButton, &[Entity])> {
+        self.pressed
+            .iter()
+            .map(|((id, button), e)| (*id, *button, e.as_slice()))
+    }
+    pub fn is_hovered(&self, entity: Entity) -> bool {
+        self.hovered.values().any(|e| e.contains(&entity))
+    }
+    /// Whether any pointer is pressing `entity` with one of `buttons`.
+    pub fn is_pressed(&self, entity: Entity, buttons: &[PointerButton]) -> bool {
+        self.pressed
+            .iter()
+            .any(|((_, button), e)| buttons.contains(button) && e.contains(&entity))
+    }
+}
+
+/// Orders the hits of one pointer topmost first, and finds the entities the pointer hovers: every
+/// entity up to and including the first one with [FocusPolicy::Block].
+///
+/// Hits are only ordered by [BackendHit::order] and depth among hits on the same render target.
+/// `targets` are the targets the pointer is over, its own location first. What is hit on an image
+/// shown by a [PickingPortal] lies on the portal's surface, so it comes just before the portal
+/// entity, and the portal is hovered even when something on the image blocks. Images the pointer
+/// is over without hitting a portal entity, e.g. through a UI node, come before everything on the
+/// pointer's own target.
+fn order_hits(
+    hits: Vec<BackendHit>,
+    targets: &[RenderTarget],
+    portals: &HashMap<Entity, RenderTarget>,
+    focus_policy: impl Fn(Entity) -> FocusPolicy,
+) -> (Vec<PointerHit>, Vec<Entity>) {
+    let mut groups: HashMap<Option<RenderTarget>, Vec<BackendHit>> = HashMap::new();
+    for hit in hits {
+        groups.entry(hit.target.clone()).or_default().push(hit);
+    }
+    for group in groups.values_mut() {
+        // Hits with a higher order, e.g. through cameras that render later, are drawn on top, so
+        // they come first. Within an order, nearer hits come first.
+        group.sort_by(|a, b| b.order.cmp(&a.order).then(a.depth.total_cmp(&b.depth)));
+    }
+    let shown_on_hit_portal = |target: &RenderTarget| {
+        groups.values().flatten().any(|hit| {
+            portals
+                .get(&hit.hit.entity)
+                .is_some_and(|image| image == target)
+        })
+    };
+    let mut order: Vec<Option<RenderTarget>> = targets
+        .iter()
+        .skip(1)
+        .filter(|target| !shown_on_hit_portal(target))
+        .map(|target| Some(target.clone()))
+        .collect();
+    order.push(targets.first().cloned());
+    order.push(None);
+
+    let mut walk = FocusWalk {
+        groups,
+        portals,
+        focus_policy: &focus_policy,
+        hits: Vec::new(),
+        hovered: Vec::new(),
+        blocked: false,
+    };
+    for target in order {
+        walk.visit(&target);
+    }
+    // Hits on targets the pointer isn't known to be over come last.
+    let mut remaining: Vec<_> = walk.groups.keys().cloned().collect();
+    remaining.sort();
+    for target in remaining {
+        walk.visit(&target);
+    }
+    (walk.hits, walk.hovered)
+}
+
+struct FocusWalk<'a> {
+    groups: HashMap<Option<RenderTarget>, Vec<BackendHit>>,
+    portals: &'a HashMap<Entity, RenderTarget>,
+    focus_policy: &'a dyn Fn(Entity) -> FocusPolicy,
+    hits: Vec<PointerHit>,
+    hovered: Vec<Entity>,
+    blocked: bool,
+}
+
+impl<'a> FocusWalk<'a> {
+    /// Walks the hits on `target`, stepping into the images of the portals hit on the way.
+    fn visit(&mut self, target: &Option<RenderTarget>) {
+        let group = match self.groups.remove(target) {
+            Some(group) => group,
+            None => return,
+        };
+        for BackendHit { hit, .. } in group {
+            let was_blocked = self.blocked;
+            if let Some(image) = self.portals.get(&hit.entity) {
+                self.visit(&Some(image.clone()));
+            }
+            self.hits.push(hit);
+            if was_blocked {
+                continue;
+            }
+            if !self.hovered.contains(&hit.entity) {
+                self.hovered.push(hit.entity);
+            }
+            if (self.focus_policy)(hit.entity) == FocusPolicy::Block {
+                self.blocked = true;
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn mesh_focus(
     paused: Option<Res<PausedForBlockers>>,
-    mouse_button_input: Res<Input<MouseButton>>,
-    touches_input: Res<Touches>,
-    pick_source_query: Query<&PickingCamera>,
+    pointers: Res<Pointers>,
+    buttons: Res<PickingButtons>,
+    mut focus: ResMut<PointerFocus>,
+    picking_hits: Res<PickingHits>,
+    roots: SelectionRoots,
+    portals: Query<(Entity, &PickingPortal)>,
     mut interactions: Query<
         (
             &mut Interaction,
@@ -74,68 +246,217 @@ pub fn mesh_focus(
             Option<&FocusPolicy>,
             Entity,
         ),
-        With<PickableMesh>,
+        With<Pickable>,
     >,
 ) {
     if let Some(paused) = paused {
         if paused.0 {
+            focus.hits.clear();
+            focus.hovered.clear();
+            focus.pressed.clear();
             return;
         }
     }
 
-    let mut hovered_entity = None;
+    focus.pressed.retain(|(pointer, button), _| {
+        pointers
+            .get(*pointer)
+            .is_some_and(|state| !state.just_released(*button))
+    });
 
-    if mouse_button_input.just_released(MouseButton::Left)
-        || touches_input.iter_just_released().next().is_some()
-    {
-        for (mut interaction, _, _, _) in &mut interactions.iter_mut() {
-            if *interaction == Interaction::Clicked {
-                *interaction = Interaction::None;
-            }
+    // Merge the hits of every backend into one list per pointer, so overlapping cameras and
+    // backends agree on what is hovered instead of the last one processed winning. Only entities
+    // that can be interacted with are hit.
+    let mut hits: HashMap<PointerId, Vec<BackendHit>> = HashMap::new();
+    for backend_hit in picking_hits.iter() {
+        if interactions.contains(backend_hit.hit.entity) {
+            hits.entry(backend_hit.hit.pointer)
+                .or_default()
+                .push(backend_hit.clone());
         }
     }
+    let portals: HashMap<Entity, RenderTarget> = portals
+        .iter()
+        .map(|(entity, portal)| (entity, RenderTarget::Image(portal.image.clone())))
+        .collect();
+    let focus_policy = |entity: Entity| {
+        interactions
+            .get(entity)
+            .ok()
+            .and_then(|(_, _, focus_policy, _)| focus_policy.cloned())
+            .unwrap_or(FocusPolicy::Block)
+    };
 
-    let mouse_clicked = mouse_button_input.just_pressed(MouseButton::Left)
-        || touches_input.iter_just_pressed().next().is_some();
-    for pick_source in pick_source_query.iter() {
-        for (topmost_entity, _intersection) in pick_source.intersections().iter() {
-            if let Ok((mut interaction, _hover, focus_policy, _entity)) =
-                interactions.get_mut(*topmost_entity)
-            {
-                if mouse_clicked {
-                    if *interaction != Interaction::Clicked {
-                        *interaction = Interaction::Clicked;
-                    }
-                } else if *interaction == Interaction::None {
-                    *interaction = Interaction::Hovered;
-                }
+    focus.hits.clear();
+    focus.hovered.clear();
+    for (pointer, hits) in hits {
+        let targets: Vec<RenderTarget> = pointers
+            .get(pointer)
+            .map(|state| state.locations().map(|l| l.target.clone()).collect())
+            .unwrap_or_default();
+        let (hits, hovered) = order_hits(hits, &targets, &portals, focus_policy);
+        focus.hits.insert(pointer, hits);
+        focus.hovered.insert(pointer, hovered);
+    }
 
-                hovered_entity = Some(*topmost_entity);
+    for (pointer, state) in pointers.iter() {
+        for button in state.iter_just_pressed() {
+            let hovered = focus.hovered(pointer).to_vec();
+            focus.pressed.insert((pointer, button), hovered);
+        }
+    }
 
-                match focus_policy.cloned().unwrap_or(FocusPolicy::Block) {
-                    FocusPolicy::Block => {
-                        break;
-                    }
-                    FocusPolicy::Pass => { /* allow the next node to be hovered/clicked */ }
-                }
+    let entity_interaction = |entity: Entity| {
+        if focus.is_pressed(entity, &buttons.press) {
+            Interaction::Clicked
+        } else if focus.is_hovered(entity) {
+            Interaction::Hovered
+        } else {
+            Interaction::None
+        }
+    };
+    // Meshes under a selection root share the strongest interaction of any of them, so the whole
+    // root is highlighted together.
+    let strength = |interaction: Interaction| match interaction {
+        Interaction::Clicked => 2,
+        Interaction::Hovered => 1,
+        Interaction::None => 0,
+    };
+    let mut root_interactions: HashMap<Entity, Interaction> = HashMap::new();
+    for (.., entity) in interactions.iter() {
+        if let Some(root) = roots.root_of(entity) {
+            let interaction = entity_interaction(entity);
+            let shared = root_interactions.entry(root).or_insert(Interaction::None);
+            if strength(interaction) > strength(*shared) {
+                *shared = interaction;
             }
         }
+    }
 
-        for (mut interaction, hover, _, entity) in &mut interactions.iter_mut() {
-            if Some(entity) != hovered_entity && *interaction == Interaction::Hovered {
-                *interaction = Interaction::None;
-            }
-            if Some(entity) == hovered_entity {
-                if let Some(mut hover) = hover {
-                    if !hover.hovered {
-                        hover.hovered = true;
-                    }
-                }
-            } else if let Some(mut hover) = hover {
-                if hover.hovered {
-                    hover.hovered = false;
-                }
+    for (mut interaction, hover, _, entity) in &mut interactions.iter_mut() {
+        let is_hovered = focus.is_hovered(entity);
+        let new_interaction = match roots.root_of(entity) {
+            Some(root) => root_interactions[&root],
+            None => entity_interaction(entity),
+        };
+        if *interaction != new_interaction {
+            *interaction = new_interaction;
+        }
+        if let Some(mut hover) = hover {
+            if hover.hovered != is_hovered {
+                hover.hovered = is_hovered;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{
+        asset::{Handle, HandleId},
+        window::WindowId,
+    };
+
+    fn window() -> RenderTarget {
+        RenderTarget::Window(WindowId::primary())
+    }
+
+    fn image() -> RenderTarget {
+        RenderTarget::Image(Handle::weak(HandleId::random::<Image>()))
+    }
+
+    fn hit(target: &RenderTarget, order: isize, depth: f32, entity: u32) -> BackendHit {
+        BackendHit {
+            target: Some(target.clone()),
+            order,
+            depth,
+            hit: PointerHit {
+                pointer: PointerId::Mouse,
+                entity: Entity::from_raw(entity),
+                position: Vec3::ZERO,
+                normal: Vec3::Z,
+                distance: depth,
+                triangle: None,
+            },
+        }
+    }
+
+    fn entities(hits: &[PointerHit]) -> Vec<u32> {
+        hits.iter().map(|hit| hit.entity.index()).collect()
+    }
+
+    fn indices(entities: &[Entity]) -> Vec<u32> {
+        entities.iter().map(|entity| entity.index()).collect()
+    }
+
+    #[test]
+    fn orders_by_priority_on_the_same_target() {
+        let window = window();
+        let hits = vec![hit(&window, 0, 1.0, 1), hit(&window, 1, 5.0, 2)];
+        let (hits, hovered) = order_hits(hits, &[window], &HashMap::new(), |_| FocusPolicy::Block);
+        assert_eq!(entities(&hits), [2, 1]);
+        assert_eq!(indices(&hovered), [2]);
+    }
+
+    #[test]
+    fn ui_portal_is_above_the_window_whatever_the_priority() {
+        let (window, image) = (window(), image());
+        // The image camera has a lower priority than the window camera behind the UI panel.
+        let hits = vec![hit(&window, 1, 5.0, 1), hit(&image, 0, 10.0, 2)];
+        let (hits, hovered) = order_hits(hits, &[window, image], &HashMap::new(), |_| {
+            FocusPolicy::Block
+        });
+        assert_eq!(entities(&hits), [2, 1]);
+        assert_eq!(indices(&hovered), [2]);
+    }
+
+    #[test]
+    fn mesh_portal_hits_come_just_before_the_portal() {
+        let (window, image) = (window(), image());
+        let monitor = Entity::from_raw(1);
+        let portals = HashMap::from_iter([(monitor, image.clone())]);
+        let hits = vec![
+            hit(&window, 1, 8.0, 3),
+            hit(&window, 1, 5.0, 1),
+            hit(&image, 0, 3.0, 2),
+        ];
+        let (hits, hovered) = order_hits(hits, &[window, image], &portals, |_| FocusPolicy::Block);
+        assert_eq!(entities(&hits), [2, 1, 3]);
+        // The monitor is hovered with what it shows, but the wall behind it is not.
+        assert_eq!(indices(&hovered), [2, 1]);
+    }
+
+    #[test]
+    fn blockers_in_front_of_a_portal_hide_what_it_shows() {
+        let (window, image) = (window(), image());
+        let monitor = Entity::from_raw(1);
+        let portals = HashMap::from_iter([(monitor, image.clone())]);
+        let hits = vec![
+            hit(&window, 0, 5.0, 1),
+            hit(&window, 0, 1.0, 3),
+            hit(&image, 0, 3.0, 2),
+        ];
+        let (hits, hovered) = order_hits(hits, &[window, image], &portals, |_| FocusPolicy::Block);
+        assert_eq!(entities(&hits), [3, 2, 1]);
+        assert_eq!(indices(&hovered), [3]);
+    }
+
+    #[test]
+    fn passing_through_a_portal_image_reaches_the_portal() {
+        let (window, image) = (window(), image());
+        let monitor = Entity::from_raw(1);
+        let portals = HashMap::from_iter([(monitor, image.clone())]);
+        let hits = vec![
+            hit(&window, 0, 5.0, 1),
+            hit(&window, 0, 8.0, 3),
+            hit(&image, 0, 3.0, 2),
+        ];
+        let policy = |entity: Entity| match entity.index() {
+            2 => FocusPolicy::Pass,
+            _ => FocusPolicy::Block,
+        };
+        let (_, hovered) = order_hits(hits, &[window, image], &portals, policy);
+        assert_eq!(indices(&hovered), [2, 1]);
+    }
+}
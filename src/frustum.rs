@@ -0,0 +1,502 @@
+use crate::{Pickable, PickableMesh, PickingCamera};
+use bevy::{
+    ecs::system::SystemParam,
+    math::{Ray, Rect},
+    prelude::*,
+    render::primitives::Aabb,
+    sprite::Mesh2dHandle,
+    utils::HashSet,
+};
+
+/// The part of a camera's view that lies behind a rectangle of its viewport, bounded by a plane
+/// through each edge of the rectangle and by the camera's near plane.
+#[derive(Debug, Clone, Copy)]
+pub struct SelectionFrustum {
+    /// Planes as a normal pointing into the frustum and a distance from the origin.
+    planes: [(Vec3, f32); 5],
+}
+
+impl SelectionFrustum {
+    /// Builds the frustum behind `rect`, given in logical pixels from the bottom-left corner of the
+    /// camera's viewport. `None` if the rectangle is empty or the camera has no viewport yet.
+    pub fn from_viewport_rect(
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        rect: Rect,
+    ) -> Option<Self> {
+        if rect.width() <= 0.0 || rect.height() <= 0.0 {
+            return None;
+        }
+        let corners = [
+            rect.min,
+            Vec2::new(rect.max.x, rect.min.y),
+            rect.max,
+            Vec2::new(rect.min.x, rect.max.y),
+        ];
+        let mut rays = [Ray::default(); 4];
+        for (ray, corner) in rays.iter_mut().zip(corners) {
+            *ray = camera.viewport_to_world(camera_transform, corner)?;
+        }
+        let center = camera.viewport_to_world(camera_transform, rect.center())?;
+        Self::from_rays(rays, center, camera_transform.forward())
+    }
+
+    /// Builds the frustum from the rays through the corners of a rectangle, in order around it,
+    /// and the ray through its center. `forward` is the direction the camera looks in.
+    fn from_rays(rays: [Ray; 4], center: Ray, forward: Vec3) -> Option<Self> {
+        let inside = center.origin + center.direction;
+
+        let mut planes = [(Vec3::ZERO, 0.0); 5];
+        for i in 0..4 {
+            let (a, b) = (rays[i], rays[(i + 1) % 4]);
+            // Both rays lie in the side plane, for perspective and orthographic projections alike.
+            let mut normal = (b.origin - a.origin).cross(a.direction).try_normalize()?;
+            if normal.dot(inside - a.origin) < 0.0 {
+                normal = -normal;
+            }
+            planes[i] = (normal, -normal.dot(a.origin));
+        }
+        planes[4] = (forward, -forward.dot(center.origin));
+        Some(Self { planes })
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|(normal, distance)| normal.dot(point) + distance >= 0.0)
+    }
+
+    /// Whether any part of `aabb`, placed in the world by `transform`, is inside the frustum. This
+    /// is conservative: boxes near the corners of the frustum may pass without touching it.
+    pub fn intersects_aabb(&self, aabb: &Aabb, transform: &GlobalTransform) -> bool {
+        let corners = aabb_corners(aabb, transform);
+        self.planes.iter().all(|(normal, distance)| {
+            corners
+                .iter()
+                .any(|corner| normal.dot(*corner) + distance >= 0.0)
+        })
+    }
+}
+
+/// Finds the [PickableMesh]es and other [Pickable] entities, such as sprites, inside a rectangle
+/// of a [PickingCamera]'s view, as a volume rather than by projecting them onto the screen.
+/// Entities without an [Aabb] are tested by their origin, and only meshes hide what is behind them.
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct FrustumSelection<'w, 's> {
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<PickingCamera>>,
+    pickables: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static GlobalTransform,
+            Option<&'static Aabb>,
+            Option<&'static Handle<Mesh>>,
+            Option<&'static Mesh2dHandle>,
+            Option<&'static ComputedVisibility>,
+        ),
+        Or<(With<PickableMesh>, With<Pickable>)>,
+    >,
+    meshes: Res<'w, Assets<Mesh>>,
+}
+
+/// The most rays [FrustumSelection::visible_entities_in_rect] casts.
+const MAX_VISIBILITY_SAMPLES: f32 = 4096.0;
+
+impl<'w, 's> FrustumSelection<'w, 's> {
+    fn frustum(&self, camera: Entity, rect: Rect) -> Option<SelectionFrustum> {
+        let (camera, transform) = self.cameras.get(camera).ok()?;
+        SelectionFrustum::from_viewport_rect(camera, transform, rect)
+    }
+
+    /// The visible pickable entities whose [Aabb], or origin if they have none, is at least partly
+    /// inside the frustum behind `rect` of `camera`'s viewport.
+    pub fn entities_in_rect(&self, camera: Entity, rect: Rect) -> Vec<Entity> {
+        match self.frustum(camera, rect) {
+            Some(frustum) => self.entities_in_frustum(&frustum),
+            None => Vec::new(),
+        }
+    }
+
+    fn entities_in_frustum(&self, frustum: &SelectionFrustum) -> Vec<Entity> {
+        self.pickables
+            .iter()
+            .filter(|(.., visibility)| visibility.map(|v| v.is_visible()).unwrap_or(true))
+            .filter(|(_, transform, aabb, ..)| match aabb {
+                Some(aabb) => frustum.intersects_aabb(aabb, transform),
+                None => frustum.contains_point(transform.translation()),
+            })
+            .map(|(entity, ..)| entity)
+            .collect()
+    }
+
+    /// Like [FrustumSelection::entities_in_rect], but leaves out entities that are hidden behind
+    /// nearer pickable meshes. Visibility is sampled by casting a ray every `spacing` logical
+    /// pixels across `rect`; an entity with a mesh is kept if it is the nearest hit of any ray.
+    /// Large rects are sampled more sparsely, so that no more than a few thousand rays are cast.
+    pub fn visible_entities_in_rect(
+        &self,
+        camera: Entity,
+        rect: Rect,
+        spacing: f32,
+    ) -> Vec<Entity> {
+        let (camera_component, camera_transform) = match self.cameras.get(camera) {
+            Ok(camera) => camera,
+            Err(_) => return Vec::new(),
+        };
+        let frustum = match self.frustum(camera, rect) {
+            Some(frustum) => frustum,
+            None => return Vec::new(),
+        };
+        let inside = self.entities_in_frustum(&frustum);
+
+        // Every sample ray lies inside the frustum, so only the meshes that reach into it can hide
+        // anything.
+        let occluders: Vec<(Entity, Mat4, &Mesh, Option<&Aabb>)> = self
+            .pickables
+            .iter()
+            .filter(|(.., visibility)| visibility.map(|v| v.is_visible()).unwrap_or(true))
+            .filter(|(_, transform, aabb, ..)| match aabb {
+                Some(aabb) => frustum.intersects_aabb(aabb, transform),
+                None => true,
+            })
+            .filter_map(|(entity, transform, aabb, mesh, mesh_2d, _)| {
+                let handle = mesh.or_else(|| mesh_2d.map(|m| &m.0))?;
+                let mesh = self.meshes.get(handle)?;
+                Some((entity, transform.compute_matrix().inverse(), mesh, aabb))
+            })
+            .collect();
+        let has_mesh: HashSet<Entity> = occluders.iter().map(|(entity, ..)| *entity).collect();
+
+        let mut visible = HashSet::new();
+        let steps = sample_steps(rect, spacing);
+        for x in 0..steps.x as u32 {
+            for y in 0..steps.y as u32 {
+                let sample = rect.min + (Vec2::new(x as f32, y as f32) + 0.5) * rect.size() / steps;
+                let ray = match camera_component.viewport_to_world(camera_transform, sample) {
+                    Some(ray) => ray,
+                    None => continue,
+                };
+                let nearest = occluders
+                    .iter()
+                    .filter_map(|(entity, world_to_local, mesh, aabb)| {
+                        let origin = world_to_local.transform_point3(ray.origin);
+                        let direction = world_to_local.transform_vector3(ray.direction);
+                        // A ray that misses the bounding box can't hit any of the triangles in it.
+                        if let Some(aabb) = aabb {
+                            ray_aabb_distance(origin, direction, aabb)?;
+                        }
+                        // Distances along the local ray match distances along the world ray, as
+                        // the direction is transformed without normalizing it.
+                        Some((*entity, ray_mesh_distance(mesh, origin, direction)?))
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                if let Some((entity, _)) = nearest {
+                    visible.insert(entity);
+                }
+            }
+        }
+
+        inside
+            .into_iter()
+            .filter(|entity| !has_mesh.contains(entity) || visible.contains(entity))
+            .collect()
+    }
+}
+
+/// How many visibility samples to take across and up `rect`: one every `spacing` pixels, but at
+/// most a sample per pixel and at most [MAX_VISIBILITY_SAMPLES] in all.
+fn sample_steps(rect: Rect, spacing: f32) -> Vec2 {
+    let steps = (rect.size() / spacing.max(1.0)).ceil().max(Vec2::ONE);
+    // Spread the samples out evenly in both directions when there are too many.
+    let scale = (MAX_VISIBILITY_SAMPLES / (steps.x * steps.y))
+        .sqrt()
+        .min(1.0);
+    let x = (steps.x * scale).floor().clamp(1.0, MAX_VISIBILITY_SAMPLES);
+    let y = (steps.y * scale)
+        .floor()
+        .clamp(1.0, (MAX_VISIBILITY_SAMPLES / x).floor());
+    Vec2::new(x, y)
+}
+
+/// The corners of `aabb` in world space.
+pub(crate) fn aabb_corners(aabb: &Aabb, transform: &GlobalTransform) -> [Vec3; 8] {
+    let (min, max) = (Vec3::from(aabb.min()), Vec3::from(aabb.max()));
+    let mut corners = [Vec3::ZERO; 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+        *corner = transform.transform_point(Vec3::new(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        ));
+    }
+    corners
+}
+
+/// The distance along the ray to where it enters `aabb`, if it hits it, or zero if the ray starts
+/// inside it.
+fn ray_aabb_distance(origin: Vec3, direction: Vec3, aabb: &Aabb) -> Option<f32> {
+    let (min, max) = (Vec3::from(aabb.min()), Vec3::from(aabb.max()));
+    let (mut near, mut far) = (f32::NEG_INFINITY, f32::INFINITY);
+    for axis in 0..3 {
+        // A ray parallel to a pair of faces is always or never between them. Dividing by zero
+        // would give infinities, or NaN for a ray starting on a face.
+        if direction[axis] == 0.0 {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let t0 = (min[axis] - origin[axis]) / direction[axis];
+        let t1 = (max[axis] - origin[axis]) / direction[axis];
+        near = near.max(t0.min(t1));
+        far = far.min(t0.max(t1));
+    }
+    (near <= far && far >= 0.0).then_some(near.max(0.0))
+}
+
+/// The distance along the ray to the nearest triangle of `mesh` it hits, if any.
+fn ray_mesh_distance(mesh: &Mesh, origin: Vec3, direction: Vec3) -> Option<f32> {
+    let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+    indices
+        .chunks_exact(3)
+        .filter_map(|triangle| {
+            let vertex = |i: usize| positions.get(i).map(|p| Vec3::from(*p));
+            let (a, b, c) = (
+                vertex(triangle[0])?,
+                vertex(triangle[1])?,
+                vertex(triangle[2])?,
+            );
+            ray_triangle_distance(origin, direction, a, b, c)
+        })
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+/// Möller–Trumbore ray-triangle intersection, hitting both sides of the triangle.
+fn ray_triangle_distance(origin: Vec3, direction: Vec3, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+    let (edge_1, edge_2) = (b - a, c - a);
+    let p = direction.cross(edge_2);
+    let determinant = edge_1.dot(p);
+    if determinant.abs() <= f32::EPSILON {
+        return None;
+    }
+    let inverse = determinant.recip();
+    let s = origin - a;
+    let u = s.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge_1);
+    let v = direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge_2.dot(q) * inverse;
+    (t >= 0.0).then_some(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    /// The corners of a rectangle, in the order [SelectionFrustum::from_viewport_rect] uses.
+    fn corners(min: Vec2, max: Vec2) -> [Vec2; 4] {
+        [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+    }
+
+    /// A perspective camera at the origin looking down -Z, with rays starting on the near plane.
+    /// `min` and `max` are the rectangle's corners on the plane one unit in front of the camera.
+    fn perspective(min: Vec2, max: Vec2, rotation: Quat) -> Option<SelectionFrustum> {
+        let near = 0.1;
+        let ray = |point: Vec2| {
+            let direction = point.extend(-1.0).normalize();
+            Ray {
+                origin: rotation * (direction * near / direction.z.abs()),
+                direction: rotation * direction,
+            }
+        };
+        SelectionFrustum::from_rays(
+            corners(min, max).map(ray),
+            ray((min + max) / 2.0),
+            rotation * Vec3::NEG_Z,
+        )
+    }
+
+    /// An orthographic camera at the origin looking down -Z, with its near plane through it.
+    fn orthographic(min: Vec2, max: Vec2) -> Option<SelectionFrustum> {
+        let ray = |point: Vec2| Ray {
+            origin: point.extend(0.0),
+            direction: Vec3::NEG_Z,
+        };
+        SelectionFrustum::from_rays(
+            corners(min, max).map(ray),
+            ray((min + max) / 2.0),
+            Vec3::NEG_Z,
+        )
+    }
+
+    fn aabb(min: [f32; 3], max: [f32; 3]) -> Aabb {
+        Aabb::from_min_max(Vec3::from(min), Vec3::from(max))
+    }
+
+    #[test]
+    fn perspective_frustum() {
+        let frustum = perspective(Vec2::NEG_ONE, Vec2::ONE, Quat::IDENTITY).unwrap();
+        assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -5.0)));
+        assert!(frustum.contains_point(Vec3::new(4.0, -4.0, -5.0)));
+        assert!(frustum.contains_point(Vec3::new(90.0, 0.0, -100.0)));
+        assert!(!frustum.contains_point(Vec3::new(6.0, 0.0, -5.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, -6.0, -5.0)));
+        // In front of the near plane, and behind the camera.
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -0.05)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 5.0)));
+    }
+
+    #[test]
+    fn off_center_perspective_frustum() {
+        let frustum =
+            perspective(Vec2::new(0.2, -0.1), Vec2::new(0.6, 0.1), Quat::IDENTITY).unwrap();
+        assert!(frustum.contains_point(Vec3::new(2.0, 0.0, -5.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -5.0)));
+        assert!(!frustum.contains_point(Vec3::new(2.0, 1.0, -5.0)));
+    }
+
+    #[test]
+    fn rotated_perspective_frustum() {
+        // Turned to look down +X.
+        let rotation = Quat::from_rotation_y(-FRAC_PI_2);
+        let frustum = perspective(Vec2::NEG_ONE, Vec2::ONE, rotation).unwrap();
+        assert!(frustum.contains_point(Vec3::new(5.0, 0.0, 0.0)));
+        assert!(frustum.contains_point(Vec3::new(5.0, 4.0, 4.0)));
+        assert!(!frustum.contains_point(Vec3::new(-5.0, 0.0, 0.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -5.0)));
+    }
+
+    #[test]
+    fn orthographic_frustum() {
+        let frustum = orthographic(Vec2::new(-2.0, -1.0), Vec2::new(2.0, 1.0)).unwrap();
+        assert!(frustum.contains_point(Vec3::new(1.5, 0.5, -100.0)));
+        assert!(frustum.contains_point(Vec3::new(2.0, 1.0, -10.0)));
+        assert!(frustum.contains_point(Vec3::ZERO));
+        assert!(!frustum.contains_point(Vec3::new(2.5, 0.0, -10.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, -1.5, -10.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn empty_rect_has_no_frustum() {
+        assert!(orthographic(Vec2::ONE, Vec2::ONE).is_none());
+        assert!(perspective(Vec2::ZERO, Vec2::ZERO, Quat::IDENTITY).is_none());
+    }
+
+    #[test]
+    fn aabbs_straddling_a_plane_intersect() {
+        let frustum = orthographic(Vec2::new(-2.0, -1.0), Vec2::new(2.0, 1.0)).unwrap();
+        let identity = GlobalTransform::IDENTITY;
+        // Across the right side plane, and across the near plane.
+        assert!(frustum.intersects_aabb(&aabb([1.0, -0.5, -6.0], [3.0, 0.5, -4.0]), &identity));
+        assert!(frustum.intersects_aabb(&aabb([-0.5, -0.5, -1.0], [0.5, 0.5, 1.0]), &identity));
+        // Enclosing the whole frustum.
+        assert!(frustum.intersects_aabb(&aabb([-9.0, -9.0, -9.0], [9.0, 9.0, 9.0]), &identity));
+        assert!(!frustum.intersects_aabb(&aabb([3.0, -0.5, -6.0], [4.0, 0.5, -4.0]), &identity));
+        assert!(!frustum.intersects_aabb(&aabb([-0.5, -0.5, 1.0], [0.5, 0.5, 2.0]), &identity));
+    }
+
+    #[test]
+    fn aabbs_are_placed_by_their_transform() {
+        let frustum = orthographic(Vec2::new(-2.0, -1.0), Vec2::new(2.0, 1.0)).unwrap();
+        let unit = aabb([-0.5, -0.5, -0.5], [0.5, 0.5, 0.5]);
+        let at = |x: f32, scale: f32| {
+            GlobalTransform::from(Transform::from_xyz(x, 0.0, -5.0).with_scale(Vec3::splat(scale)))
+        };
+        assert!(frustum.intersects_aabb(&unit, &at(2.4, 1.0)));
+        assert!(!frustum.intersects_aabb(&unit, &at(2.6, 1.0)));
+        assert!(frustum.intersects_aabb(&unit, &at(2.6, 2.0)));
+    }
+
+    #[test]
+    fn ray_aabb_distance_enters_the_box() {
+        let unit = aabb([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]);
+        let distance = |origin: [f32; 3], direction: [f32; 3]| {
+            ray_aabb_distance(Vec3::from(origin), Vec3::from(direction), &unit)
+        };
+        assert_eq!(distance([0.0, 0.0, 5.0], [0.0, 0.0, -1.0]), Some(4.0));
+        assert_eq!(distance([5.0, 5.0, 5.0], [-1.0, -1.0, -1.0]), Some(4.0));
+        assert_eq!(distance([0.0, 0.0, 0.0], [0.0, 0.0, -1.0]), Some(0.0));
+        assert_eq!(distance([0.0, 0.0, 5.0], [0.0, 0.0, 1.0]), None);
+        assert_eq!(distance([3.0, 0.0, 5.0], [0.0, 0.0, -1.0]), None);
+    }
+
+    #[test]
+    fn ray_aabb_distance_with_zero_direction_components() {
+        let unit = aabb([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]);
+        let distance = |origin: [f32; 3], direction: [f32; 3]| {
+            ray_aabb_distance(Vec3::from(origin), Vec3::from(direction), &unit)
+        };
+        // Starting on a face the ray runs parallel to, which would give 0 * inf = NaN.
+        assert_eq!(distance([1.0, 0.0, 5.0], [0.0, 0.0, -1.0]), Some(4.0));
+        assert_eq!(distance([1.0, -1.0, 5.0], [0.0, -0.0, -1.0]), Some(4.0));
+        assert_eq!(distance([1.5, 0.0, 5.0], [0.0, 0.0, -1.0]), None);
+        assert_eq!(distance([0.0, 1.5, 5.0], [0.0, 0.0, -1.0]), None);
+        assert_eq!(distance([0.5, 0.5, 0.5], [0.0, 0.0, 0.0]), Some(0.0));
+        assert_eq!(distance([0.5, 0.5, 5.0], [0.0, 0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn ray_triangle_distance_hits_both_sides() {
+        let (a, b, c) = (
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let distance =
+            |origin: Vec3, direction: Vec3| ray_triangle_distance(origin, direction, a, b, c);
+        assert_eq!(distance(Vec3::new(0.0, 0.0, 5.0), Vec3::NEG_Z), Some(5.0));
+        assert_eq!(distance(Vec3::new(0.0, 0.0, -5.0), Vec3::Z), Some(5.0));
+        // Distances are measured in lengths of `direction`.
+        assert_eq!(
+            distance(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0)),
+            Some(2.5)
+        );
+        // On an edge.
+        assert_eq!(distance(Vec3::new(0.0, -1.0, 5.0), Vec3::NEG_Z), Some(5.0));
+    }
+
+    #[test]
+    fn ray_triangle_distance_misses() {
+        let (a, b, c) = (
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let distance =
+            |origin: Vec3, direction: Vec3| ray_triangle_distance(origin, direction, a, b, c);
+        assert_eq!(distance(Vec3::new(2.0, 0.0, 5.0), Vec3::NEG_Z), None);
+        assert_eq!(distance(Vec3::new(0.0, 0.0, -5.0), Vec3::NEG_Z), None);
+        // Parallel to the triangle, in and out of its plane.
+        assert_eq!(distance(Vec3::new(-5.0, 0.0, 0.0), Vec3::X), None);
+        assert_eq!(distance(Vec3::new(-5.0, 0.0, 1.0), Vec3::X), None);
+    }
+
+    #[test]
+    fn sample_steps_are_clamped() {
+        let small = Rect::new(0.0, 0.0, 100.0, 40.0);
+        assert_eq!(sample_steps(small, 4.0), Vec2::new(25.0, 10.0));
+        assert_eq!(sample_steps(small, 0.0), Vec2::new(100.0, 40.0));
+        for rect in [
+            Rect::new(0.0, 0.0, 4000.0, 3000.0),
+            Rect::new(0.0, 0.0, 100_000.0, 1.0),
+            Rect::new(0.0, 0.0, 1.0, 100_000.0),
+        ] {
+            let steps = sample_steps(rect, 1.0);
+            assert!(steps.x * steps.y <= MAX_VISIBILITY_SAMPLES, "{steps}");
+            assert!(steps.x * steps.y >= MAX_VISIBILITY_SAMPLES * 0.9, "{steps}");
+        }
+    }
+}
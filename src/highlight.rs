@@ -89,36 +89,33 @@ pub fn get_initial_mesh_highlight_asset<T: Asset>(
 pub fn mesh_highlighting<T: 'static + Highlightable + Send + Sync>(
     paused: Option<Res<PausedForBlockers>>,
     global_default_highlight: Res<DefaultHighlighting<T>>,
-    mut interaction_query: Query<
-        (
-            &Interaction,
-            &mut Handle<T>,
-            Option<&Selection>,
-            &Highlighting<T>,
-        ),
-        Or<(Changed<Interaction>, Changed<Selection>)>,
-    >,
+    roots: SelectionRoots,
+    selection_query: Query<(&Selection, ChangeTrackers<Selection>)>,
+    mut interaction_query: Query<(
+        Entity,
+        &Interaction,
+        ChangeTrackers<Interaction>,
+        &mut Handle<T>,
+        &Highlighting<T>,
+    )>,
 ) {
-    // Set non-hovered material when picking is paused (e.g. while hovering a picking blocker).
-    if let Some(paused) = paused {
-        if paused.is_paused() {
-            for (_, mut material, selection, highlight) in interaction_query.iter_mut() {
-                *material = if selection.filter(|s| s.selected()).is_some() {
-                    if let Some(highlight_asset) = &highlight.selected {
-                        highlight_asset
-                    } else {
-                        &global_default_highlight.selected
-                    }
-                } else {
-                    &highlight.initial
-                }
-                .to_owned();
-            }
-            return;
+    let paused = paused.is_some_and(|paused| paused.is_paused());
+    for (entity, interaction, interaction_change, mut material, highlight) in
+        interaction_query.iter_mut()
+    {
+        // Meshes under a selection root show the root's selection.
+        let selection = selection_query.get(roots.resolve(entity)).ok();
+        let selection_changed = selection.is_some_and(|(_, change)| change.is_changed());
+        if !interaction_change.is_changed() && !selection_changed {
+            continue;
         }
-    }
-    for (interaction, mut material, selection, highlight) in interaction_query.iter_mut() {
-        *material = match *interaction {
+        let selected = selection.is_some_and(|(selection, _)| selection.selected());
+        // Set non-hovered material when picking is paused (e.g. while hovering a picking blocker).
+        let interaction = match paused {
+            true => Interaction::None,
+            false => *interaction,
+        };
+        *material = match interaction {
             Interaction::Clicked => {
                 if let Some(highlight_asset) = &highlight.pressed {
                     highlight_asset
@@ -134,7 +131,7 @@ pub fn mesh_highlighting<T: 'static + Highlightable + Send + Sync>(
                 }
             }
             Interaction::None => {
-                if selection.filter(|s| s.selected()).is_some() {
+                if selected {
                     if let Some(highlight_asset) = &highlight.selected {
                         highlight_asset
                     } else {
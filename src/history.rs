@@ -0,0 +1,237 @@
+use crate::Selection;
+use bevy::{ecs::system::Command, prelude::*, utils::HashSet};
+use std::collections::VecDeque;
+
+/// The selection changes made in one frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionTransaction {
+    /// Entities that were selected.
+    pub added: Vec<Entity>,
+    /// Entities that were deselected.
+    pub removed: Vec<Entity>,
+}
+
+impl SelectionTransaction {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+    fn inverse(&self) -> Self {
+        Self {
+            added: self.removed.clone(),
+            removed: self.added.clone(),
+        }
+    }
+}
+
+/// A bounded history of [SelectionTransaction]s, recorded by [record_selection_history] and
+/// walked with the [UndoSelection] and [RedoSelection] commands.
+///
+/// Selecting an entity when it is spawned is not recorded, and entities that have been despawned
+/// are skipped when undoing or redoing.
+#[derive(Debug, Clone, Resource)]
+pub struct SelectionHistory {
+    /// The most transactions that can be undone. The oldest are forgotten first.
+    pub capacity: usize,
+    undo: VecDeque<SelectionTransaction>,
+    redo: Vec<SelectionTransaction>,
+    /// The selected entities, as of the last recorded transaction.
+    selected: HashSet<Entity>,
+}
+
+impl Default for SelectionHistory {
+    fn default() -> Self {
+        Self {
+            capacity: 100,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            selected: HashSet::default(),
+        }
+    }
+}
+
+impl SelectionHistory {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    /// The transactions that can be undone, oldest first.
+    pub fn undo_stack(&self) -> impl DoubleEndedIterator<Item = &SelectionTransaction> {
+        self.undo.iter()
+    }
+    /// The transactions that can be redone, the next one last.
+    pub fn redo_stack(&self) -> impl DoubleEndedIterator<Item = &SelectionTransaction> {
+        self.redo.iter()
+    }
+    /// Forgets every transaction, keeping the current selection.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+    fn push(&mut self, transaction: SelectionTransaction) {
+        self.redo.clear();
+        self.undo.push_back(transaction);
+        while self.undo.len() > self.capacity {
+            self.undo.pop_front();
+        }
+    }
+}
+
+/// Groups the [Selection] changes made in a frame into one [SelectionTransaction]. It runs at the
+/// end of [CoreStage::Last], so clicks, area selections and commands from anywhere in the frame
+/// are recorded together.
+pub fn record_selection_history(
+    mut history: ResMut<SelectionHistory>,
+    changed: Query<(Entity, &Selection, ChangeTrackers<Selection>), Changed<Selection>>,
+    selections: Query<(), With<Selection>>,
+) {
+    // Forget entities that were despawned or lost their selection, so they are not recorded as
+    // deselected if they come back.
+    history
+        .selected
+        .retain(|entity| selections.contains(*entity));
+    let mut transaction = SelectionTransaction::default();
+    for (entity, selection, tracker) in changed.iter() {
+        // Undo and redo update the recorded state themselves, so their changes are not recorded.
+        let was_selected = history.selected.contains(&entity);
+        if selection.selected() == was_selected {
+            continue;
+        }
+        // Adding a selection, e.g. when an entity is spawned or the component is added back after
+        // being removed, is not a change to record.
+        if tracker.is_added() {
+            match selection.selected() {
+                true => history.selected.insert(entity),
+                false => history.selected.remove(&entity),
+            };
+        } else if selection.selected() {
+            history.selected.insert(entity);
+            transaction.added.push(entity);
+        } else {
+            history.selected.remove(&entity);
+            transaction.removed.push(entity);
+        }
+    }
+    if !transaction.is_empty() {
+        history.push(transaction);
+    }
+}
+
+/// Reverts the last [SelectionTransaction] in the [SelectionHistory].
+pub struct UndoSelection;
+
+impl Command for UndoSelection {
+    fn write(self, world: &mut World) {
+        let transaction = match world.get_resource_mut::<SelectionHistory>() {
+            Some(mut history) => history.undo.pop_back(),
+            None => return,
+        };
+        if let Some(transaction) = transaction {
+            apply_transaction(world, &transaction.inverse());
+            world
+                .resource_mut::<SelectionHistory>()
+                .redo
+                .push(transaction);
+        }
+    }
+}
+
+/// Reapplies the last [SelectionTransaction] undone with [UndoSelection].
+pub struct RedoSelection;
+
+impl Command for RedoSelection {
+    fn write(self, world: &mut World) {
+        let transaction = match world.get_resource_mut::<SelectionHistory>() {
+            Some(mut history) => history.redo.pop(),
+            None => return,
+        };
+        if let Some(transaction) = transaction {
+            apply_transaction(world, &transaction);
+            world
+                .resource_mut::<SelectionHistory>()
+                .undo
+                .push_back(transaction);
+        }
+    }
+}
+
+/// Selects and deselects the entities of `transaction` that still exist, changing only the
+/// [Selection]s whose state differs so the usual [SelectionEvent](crate::SelectionEvent)s are sent.
+fn apply_transaction(world: &mut World, transaction: &SelectionTransaction) {
+    let changes = transaction
+        .added
+        .iter()
+        .map(|entity| (*entity, true))
+        .chain(transaction.removed.iter().map(|entity| (*entity, false)));
+    for (entity, selected) in changes {
+        let applied = match world.get_mut::<Selection>(entity) {
+            Some(mut selection) => {
+                if selection.selected() != selected {
+                    selection.set_selected(selected);
+                }
+                true
+            }
+            None => false,
+        };
+        if applied {
+            let mut history = world.resource_mut::<SelectionHistory>();
+            match selected {
+                true => history.selected.insert(entity),
+                false => history.selected.remove(&entity),
+            };
+        }
+    }
+}
+
+/// Undo and redo for selection changes on [Commands].
+pub trait SelectionHistoryCommands {
+    /// Reverts the last selection change, sending the matching
+    /// [SelectionEvent](crate::SelectionEvent)s.
+    fn undo_selection(&mut self);
+    /// Reapplies the last undone selection change.
+    fn redo_selection(&mut self);
+}
+
+impl<'w, 's> SelectionHistoryCommands for Commands<'w, 's> {
+    fn undo_selection(&mut self) {
+        self.add(UndoSelection);
+    }
+    fn redo_selection(&mut self) {
+        self.add(RedoSelection);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select_first(mut selections: Query<&mut Selection>) {
+        selections.iter_mut().next().unwrap().set_selected(true);
+    }
+
+    fn select_last(mut selections: Query<&mut Selection>) {
+        selections.iter_mut().last().unwrap().set_selected(true);
+    }
+
+    #[test]
+    fn one_transaction_per_frame() {
+        let mut app = App::new();
+        app.init_resource::<SelectionHistory>()
+            .add_system_to_stage(CoreStage::Last, record_selection_history.at_end());
+        let a = app.world.spawn(Selection::default()).id();
+        let b = app.world.spawn(Selection::default()).id();
+        app.update();
+        assert!(!app.world.resource::<SelectionHistory>().can_undo());
+
+        app.add_system_to_stage(CoreStage::First, select_first)
+            .add_system_to_stage(CoreStage::Update, select_last);
+        app.update();
+        let history = app.world.resource::<SelectionHistory>();
+        let transactions: Vec<_> = history.undo_stack().collect();
+        assert_eq!(transactions.len(), 1);
+        let mut added = transactions[0].added.clone();
+        added.sort();
+        assert_eq!(added, vec![a, b]);
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{
+    marquee::{projected_corners, start_area_selection, AreaSelectionStart},
+    selection::select_area,
+    DragSettings, PausedForBlockers, PickingButtons, PickingCamera, PointerFocus, Pointers,
+    Selection, SelectionBindings, SelectionFilter, SelectionMode, SelectionRoots, SelectionScope,
+    SelectionScopes,
+};
+use bevy::{
+    prelude::*,
+    render::{camera::RenderTarget, primitives::Aabb},
+    utils::HashSet,
+};
+
+/// What part of an entity has to be inside the lasso for the entity to be selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LassoTest {
+    /// The projected origin of the entity.
+    #[default]
+    Center,
+    /// Every projected corner of the entity's [Aabb].
+    Bounds,
+}
+
+/// A freeform path being drawn by a pointer to select everything inside it.
+#[derive(Debug, Clone)]
+struct ActiveLasso {
+    start: AreaSelectionStart,
+    path: Vec<Vec2>,
+    mode: SelectionMode,
+    dragging: bool,
+}
+
+/// The lasso selection, started by pressing a [PickingButtons::select] button over empty space and
+/// drawing a path around the entities to select. On release the path is closed into a polygon, and
+/// every [Selection] entity inside it is selected, as chosen by the [SelectionBindings] chords held
+/// when the lasso started.
+#[derive(Debug, Resource)]
+pub struct Lasso {
+    /// Whether presses over empty space start a lasso. While enabled, the lasso replaces the
+    /// [Marquee](crate::Marquee). [LassoSelectionPlugin](crate::LassoSelectionPlugin) switches it
+    /// on; it is off in [Lasso::default], so a lasso inserted by hand starts out switched off.
+    pub enabled: bool,
+    pub test: LassoTest,
+    /// The shortest distance, in logical pixels, between two points recorded on the path.
+    pub spacing: f32,
+    active: Option<ActiveLasso>,
+}
+
+impl Default for Lasso {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            test: LassoTest::default(),
+            spacing: 2.0,
+            active: None,
+        }
+    }
+}
+
+impl Lasso {
+    /// The path drawn so far, in logical pixels from the bottom-left corner of [Lasso::target].
+    /// Empty until the pointer moves further than [DragSettings::threshold].
+    pub fn path(&self) -> &[Vec2] {
+        match &self.active {
+            Some(lasso) if lasso.dragging => lasso.path.as_slice(),
+            _ => &[],
+        }
+    }
+    /// The render target the lasso is drawn on.
+    pub fn target(&self) -> Option<&RenderTarget> {
+        self.active.as_ref().map(|lasso| &lasso.start.target)
+    }
+    /// The camera whose view the lasso selects from.
+    pub fn camera(&self) -> Option<Entity> {
+        self.active.as_ref().map(|lasso| lasso.start.camera)
+    }
+    pub fn mode(&self) -> Option<SelectionMode> {
+        self.active.as_ref().map(|lasso| lasso.mode)
+    }
+}
+
+/// Starts, records and finishes the [Lasso] selection.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn lasso_selection(
+    paused: Option<Res<PausedForBlockers>>,
+    pointers: Res<Pointers>,
+    focus: Res<PointerFocus>,
+    buttons: Res<PickingButtons>,
+    bindings: Res<SelectionBindings>,
+    scopes: Res<SelectionScopes>,
+    filter: Res<SelectionFilter>,
+    roots: SelectionRoots,
+    drag_settings: Res<DragSettings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut lasso: ResMut<Lasso>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform), With<PickingCamera>>,
+    mut selections: Query<(
+        Entity,
+        &mut Selection,
+        &GlobalTransform,
+        Option<&Aabb>,
+        Option<&ComputedVisibility>,
+        Option<&SelectionScope>,
+    )>,
+) {
+    if lasso.enabled && lasso.active.is_none() && !paused.is_some_and(|paused| paused.0) {
+        lasso.active =
+            start_area_selection(&pointers, &focus, &buttons, &cameras).map(|start| ActiveLasso {
+                path: vec![start.position],
+                start,
+                mode: bindings.mode(&keyboard_input, &mouse_input),
+                dragging: false,
+            });
+    }
+
+    let spacing = lasso.spacing;
+    let active = match lasso.active.as_mut() {
+        Some(active) => active,
+        None => return,
+    };
+    let state = pointers.get(active.start.pointer);
+    if let Some(location) = state.and_then(|state| state.location_on(&active.start.target)) {
+        let last = active.path.last().copied().unwrap_or(location.position);
+        if last.distance(location.position) >= spacing {
+            active.path.push(location.position);
+        }
+        if active.start.position.distance(location.position) >= drag_settings.threshold {
+            active.dragging = true;
+        }
+    }
+    if state.is_some_and(|state| state.pressed(active.start.button)) {
+        return;
+    }
+
+    let active = match lasso.active.take() {
+        Some(active) if active.dragging && active.path.len() >= 3 => active,
+        _ => return,
+    };
+    let (camera, camera_transform) = match cameras.get(active.start.camera) {
+        Ok((_, camera, transform)) => (camera, transform),
+        Err(_) => return,
+    };
+    let offset = active.start.viewport_offset;
+    let polygon: Vec<Vec2> = active.path.iter().map(|point| *point - offset).collect();
+    let inside: HashSet<Entity> = selections
+        .iter()
+        .filter(|(.., visibility, _)| visibility.map(|v| v.is_visible()).unwrap_or(true))
+        .filter_map(|(entity, _, transform, aabb, ..)| {
+            let aabb = match lasso.test {
+                LassoTest::Center => None,
+                LassoTest::Bounds => aabb,
+            };
+            let corners = projected_corners(camera, camera_transform, transform, aabb)?;
+            corners
+                .iter()
+                .all(|corner| polygon_contains(&polygon, *corner))
+                .then_some(entity)
+        })
+        .collect();
+    select_area(
+        selections
+            .iter_mut()
+            .map(|(entity, selection, .., scope)| (entity, selection, scope)),
+        &inside,
+        active.mode,
+        &scopes,
+        &filter,
+        |entity| roots.resolve(entity),
+    );
+}
+
+/// Whether `point` is inside the closed polygon, using the even-odd rule. Points on the edges count
+/// as inside, as they do for the [Marquee](crate::Marquee)'s rect.
+fn polygon_contains(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    let mut previous = match polygon.last() {
+        Some(last) => *last,
+        None => return false,
+    };
+    for current in polygon.iter().copied() {
+        if on_segment(previous, current, point) {
+            return true;
+        }
+        if (current.y > point.y) != (previous.y > point.y) {
+            let crossing = current.x
+                + (point.y - current.y) / (previous.y - current.y) * (previous.x - current.x);
+            if point.x < crossing {
+                inside = !inside;
+            }
+        }
+        previous = current;
+    }
+    inside
+}
+
+/// Whether `point` lies on the segment from `a` to `b`, allowing for rounding.
+fn on_segment(a: Vec2, b: Vec2, point: Vec2) -> bool {
+    let ab = b - a;
+    let t = match ab.length_squared() {
+        length if length > 0.0 => ((point - a).dot(ab) / length).clamp(0.0, 1.0),
+        _ => 0.0,
+    };
+    (a + ab * t).distance_squared(point) <= 1e-6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{update_pointers, LassoSelectionPlugin, PickingPluginsState, PickingSystem};
+    use bevy::{
+        render::camera::camera_system,
+        window::{CursorMoved, WindowCreated, WindowId, WindowResized},
+    };
+
+    /// A U shape, open at the top between x = 10 and x = 20.
+    fn u_shape() -> Vec<Vec2> {
+        [
+            (0.0, 0.0),
+            (30.0, 0.0),
+            (30.0, 30.0),
+            (20.0, 30.0),
+            (20.0, 10.0),
+            (10.0, 10.0),
+            (10.0, 30.0),
+            (0.0, 30.0),
+        ]
+        .into_iter()
+        .map(Vec2::from)
+        .collect()
+    }
+
+    #[test]
+    fn concave_polygon() {
+        let polygon = u_shape();
+        assert!(polygon_contains(&polygon, Vec2::new(5.0, 20.0)));
+        assert!(polygon_contains(&polygon, Vec2::new(25.0, 20.0)));
+        assert!(polygon_contains(&polygon, Vec2::new(15.0, 5.0)));
+        // Inside the notch, between the arms.
+        assert!(!polygon_contains(&polygon, Vec2::new(15.0, 20.0)));
+        assert!(!polygon_contains(&polygon, Vec2::new(35.0, 20.0)));
+        assert!(!polygon_contains(&polygon, Vec2::new(15.0, -5.0)));
+    }
+
+    #[test]
+    fn points_on_edges_are_inside() {
+        let polygon = u_shape();
+        for point in [
+            (0.0, 15.0),
+            (30.0, 15.0),
+            (15.0, 0.0),
+            (15.0, 10.0),
+            (10.0, 20.0),
+            (20.0, 20.0),
+            (25.0, 30.0),
+        ] {
+            assert!(polygon_contains(&polygon, Vec2::from(point)), "{point:?}");
+        }
+    }
+
+    #[test]
+    fn points_on_vertices_are_inside() {
+        let polygon = u_shape();
+        for point in polygon.iter() {
+            assert!(polygon_contains(&polygon, *point), "{point:?}");
+        }
+    }
+
+    #[test]
+    fn rays_through_vertices_count_once() {
+        // A diamond, whose left and right vertices lie level with the points tested.
+        let polygon: Vec<Vec2> = [(0.0, 10.0), (10.0, 0.0), (20.0, 10.0), (10.0, 20.0)]
+            .into_iter()
+            .map(Vec2::from)
+            .collect();
+        assert!(polygon_contains(&polygon, Vec2::new(10.0, 10.0)));
+        assert!(!polygon_contains(&polygon, Vec2::new(-5.0, 10.0)));
+        assert!(!polygon_contains(&polygon, Vec2::new(25.0, 10.0)));
+    }
+
+    #[test]
+    fn degenerate_polygons() {
+        assert!(!polygon_contains(&[], Vec2::ZERO));
+        assert!(polygon_contains(&[Vec2::ONE], Vec2::ONE));
+        assert!(!polygon_contains(&[Vec2::ONE], Vec2::ZERO));
+        let line = [Vec2::ZERO, Vec2::new(10.0, 0.0)];
+        assert!(polygon_contains(&line, Vec2::new(5.0, 0.0)));
+        assert!(!polygon_contains(&line, Vec2::new(5.0, 1.0)));
+    }
+
+    /// An app with just the lasso and what it needs, drawing in an 800x600 window through a 2D
+    /// camera centered on the origin.
+    fn lasso_app() -> App {
+        let mut app = App::new();
+        app.add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            .add_event::<CursorMoved>()
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .init_resource::<Windows>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<Touches>()
+            .init_resource::<PickingPluginsState>()
+            .init_resource::<Pointers>()
+            .init_resource::<PointerFocus>()
+            .init_resource::<PickingButtons>()
+            .init_resource::<SelectionBindings>()
+            .init_resource::<SelectionScopes>()
+            .init_resource::<SelectionFilter>()
+            .init_resource::<DragSettings>()
+            // Stands in for the focus, which the lasso runs after.
+            .add_system_to_stage(
+                CoreStage::First,
+                update_pointers.label(PickingSystem::Focus),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera_system::<OrthographicProjection>,
+            )
+            .add_plugin(LassoSelectionPlugin);
+        app.world.resource_mut::<Windows>().add(Window::new(
+            WindowId::primary(),
+            &WindowDescriptor::default(),
+            800,
+            600,
+            1.0,
+            None,
+            None,
+        ));
+        app.world.spawn((
+            Camera::default(),
+            OrthographicProjection::default(),
+            GlobalTransform::default(),
+            PickingCamera::new(),
+        ));
+        app
+    }
+
+    fn move_mouse(app: &mut App, position: Vec2) {
+        app.world.send_event(CursorMoved {
+            id: WindowId::primary(),
+            position,
+        });
+        app.update();
+    }
+
+    #[test]
+    fn plugin_selects_with_default_settings() {
+        let mut app = lasso_app();
+        let entity = app
+            .world
+            .spawn((
+                Selection::default(),
+                GlobalTransform::from_xyz(0.0, 0.0, -1.0),
+            ))
+            .id();
+        let outside = app
+            .world
+            .spawn((
+                Selection::default(),
+                GlobalTransform::from_xyz(200.0, 0.0, -1.0),
+            ))
+            .id();
+        // The origin is in the middle of the window.
+        move_mouse(&mut app, Vec2::new(350.0, 250.0));
+        app.world
+            .resource_mut::<Input<MouseButton>>()
+            .press(MouseButton::Left);
+        app.update();
+        for corner in [(450.0, 250.0), (450.0, 350.0), (350.0, 350.0)] {
+            move_mouse(&mut app, Vec2::from(corner));
+        }
+        app.world
+            .resource_mut::<Input<MouseButton>>()
+            .release(MouseButton::Left);
+        app.update();
+
+        assert!(app.world.get::<Selection>(entity).unwrap().selected());
+        assert!(!app.world.get::<Selection>(outside).unwrap().selected());
+    }
+}
@@ -1,17 +1,64 @@
+pub mod backend;
+pub mod bubbling;
+pub mod drag;
 pub mod events;
 pub mod focus;
+pub mod frustum;
 pub mod highlight;
+pub mod history;
+pub mod lasso;
+pub mod listener;
+pub mod marquee;
 pub mod mouse;
+pub mod pointer;
+pub mod portal;
 pub mod selection;
+#[cfg(feature = "2d")]
+pub mod sprite;
 
 use std::marker::PhantomData;
 
+#[cfg(feature = "2d")]
+pub use crate::sprite::{sprite_hits, PickableSprite, PickableSpriteBundle, SpriteBackend};
 pub use crate::{
-    events::{event_debug_system, mesh_events_system, HoverEvent, PickingEvent, SelectionEvent},
-    focus::{mesh_focus, pause_for_picking_blockers, Hover, PickingBlocker},
+    backend::{
+        clear_picking_hits, raycast_hits, BackendHit, PickingBackend, PickingBackendPlugin,
+        PickingHits, RaycastBackend,
+    },
+    bubbling::{bubble_picking_events, BubbledEvent, StopPropagation},
+    drag::{mesh_drag_events, DragSettings, PointerDrags},
+    events::{
+        clicked_nothing_events, event_debug_system, mesh_events_system, removed_pickable_events,
+        ClickEvent, ClickSettings, DragEvent, EnteredHovers, HoverEvent, PickingEvent,
+        SelectionEvent,
+    },
+    focus::{
+        mesh_focus, pause_for_picking_blockers, Hover, Pickable, PickingBlocker, PointerFocus,
+        PointerHit,
+    },
+    frustum::{FrustumSelection, SelectionFrustum},
     highlight::{mesh_highlighting, DefaultHighlighting, Highlightable, Highlighting},
-    mouse::update_pick_source_positions,
-    selection::{mesh_selection, NoDeselect, Selection},
+    history::{
+        record_selection_history, RedoSelection, SelectionHistory, SelectionHistoryCommands,
+        SelectionTransaction, UndoSelection,
+    },
+    lasso::{lasso_selection, Lasso, LassoTest},
+    listener::{
+        run_event_listeners, EventListener, ListenerInput, OnClick, OnDragEnd, OnHover, OnSelect,
+    },
+    marquee::{marquee_selection, Marquee, MarqueeTest},
+    mouse::{spawn_pointer_rays, update_pick_source_positions, update_pointer_rays, PointerRay},
+    pointer::{
+        update_pointers, PickingButtons, PointerButton, PointerId, PointerLocation, PointerState,
+        Pointers,
+    },
+    portal::{update_portal_locations, PickingPortal, PortalMapping},
+    selection::{
+        mesh_selection, update_selection_filter, update_selection_set, Chord, NoDeselect,
+        ScopeMode, Selection, SelectionBindings, SelectionCommands, SelectionFilter, SelectionHits,
+        SelectionLock, SelectionMode, SelectionRoot, SelectionRoots, SelectionScope,
+        SelectionScopes, SelectionSet,
+    },
 };
 pub use bevy_mod_raycast::{Primitive3d, RaycastMesh, RaycastSource};
 
@@ -20,15 +67,21 @@ use highlight::{get_initial_mesh_highlight_asset, Highlight};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
 pub enum PickingSystem {
+    UpdatePointers,
     UpdatePickSourcePositions,
     BuildRays,
     UpdateRaycast,
     UpdateIntersections,
+    /// Every [PickingBackend] system, which fills [PickingHits].
+    Backend,
     Highlighting,
     Selection,
+    UpdateSelectionSet,
     PauseForBlockers,
     Focus,
+    Drag,
     Events,
+    Bubbling,
 }
 
 /// A type alias for the concrete [RaycastMesh](bevy_mod_raycast::RaycastMesh) type used for Picking.
@@ -103,32 +156,47 @@ pub struct PickingPlugin;
 impl Plugin for PickingPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<PickingPluginsState>()
+            .init_resource::<Pointers>()
+            .init_resource::<PickingHits>()
+            .add_system_to_stage(
+                CoreStage::First,
+                update_pointers.label(PickingSystem::UpdatePointers),
+            )
+            .add_system_to_stage(CoreStage::Last, spawn_pointer_rays)
             .add_system_set_to_stage(
                 CoreStage::First,
                 SystemSet::new()
                     .with_run_criteria(|state: Res<PickingPluginsState>| {
                         simple_criteria(state.enable_picking)
                     })
+                    .with_system(
+                        // Portals read the previous frame's hits, so they run before those are
+                        // cleared.
+                        update_portal_locations
+                            .after(PickingSystem::UpdatePointers)
+                            .before(PickingSystem::UpdatePickSourcePositions),
+                    )
                     .with_system(
                         update_pick_source_positions
                             .label(PickingSystem::UpdatePickSourcePositions)
+                            .after(PickingSystem::UpdatePointers)
                             .before(PickingSystem::BuildRays),
                     )
                     .with_system(
-                        bevy_mod_raycast::build_rays::<PickingRaycastSet>
-                            .label(PickingSystem::BuildRays)
-                            .before(PickingSystem::UpdateRaycast),
-                    )
-                    .with_system(
-                        bevy_mod_raycast::update_raycast::<PickingRaycastSet>
-                            .label(PickingSystem::UpdateRaycast)
-                            .before(PickingSystem::UpdateIntersections),
+                        update_pointer_rays
+                            .label(PickingSystem::UpdatePickSourcePositions)
+                            .after(PickingSystem::UpdatePointers)
+                            .before(PickingSystem::BuildRays),
                     )
                     .with_system(
-                        bevy_mod_raycast::update_intersections::<PickingRaycastSet>
-                            .label(PickingSystem::UpdateIntersections),
+                        clear_picking_hits
+                            .after(PickingSystem::UpdatePickSourcePositions)
+                            .before(PickingSystem::Backend),
                     ),
-            );
+            )
+            .add_plugin(PickingBackendPlugin::<RaycastBackend>::default());
+        #[cfg(feature = "2d")]
+        app.add_plugin(PickingBackendPlugin::<SpriteBackend>::default());
     }
 }
 
@@ -136,7 +204,20 @@ pub struct InteractablePickingPlugin;
 impl Plugin for InteractablePickingPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<PausedForBlockers>()
+            .init_resource::<PointerFocus>()
+            .init_resource::<PickingButtons>()
+            .init_resource::<SelectionBindings>()
+            .init_resource::<SelectionScopes>()
+            .init_resource::<SelectionFilter>()
+            .init_resource::<SelectionSet>()
+            .init_resource::<SelectionHits>()
+            .init_resource::<SelectionHistory>()
+            .init_resource::<ClickSettings>()
+            .init_resource::<DragSettings>()
+            .init_resource::<PointerDrags>()
+            .init_resource::<EnteredHovers>()
             .add_event::<PickingEvent>()
+            .add_event::<BubbledEvent>()
             .add_system_set_to_stage(
                 CoreStage::First,
                 SystemSet::new()
@@ -146,23 +227,61 @@ impl Plugin for InteractablePickingPlugin {
                     .with_system(
                         pause_for_picking_blockers
                             .label(PickingSystem::PauseForBlockers)
-                            .after(PickingSystem::UpdateIntersections),
+                            .after(PickingSystem::Backend),
                     )
                     .with_system(
                         mesh_focus
                             .label(PickingSystem::Focus)
                             .after(PickingSystem::PauseForBlockers),
                     )
+                    .with_system(update_selection_filter.at_start())
                     .with_system(
                         mesh_selection
                             .label(PickingSystem::Selection)
                             .after(PickingSystem::Focus),
                     )
+                    .with_system(
+                        update_selection_set
+                            .label(PickingSystem::UpdateSelectionSet)
+                            .after(PickingSystem::Selection)
+                            .before(PickingSystem::Events),
+                    )
+                    .with_system(
+                        mesh_drag_events
+                            .label(PickingSystem::Drag)
+                            .after(PickingSystem::Focus)
+                            .before(PickingSystem::Events),
+                    )
                     .with_system(
                         mesh_events_system
                             .label(PickingSystem::Events)
                             .after(PickingSystem::Selection),
-                    ),
+                    )
+                    .with_system(
+                        clicked_nothing_events
+                            .label(PickingSystem::Events)
+                            .after(PickingSystem::Selection),
+                    )
+                    .with_system(
+                        bubble_picking_events
+                            .label(PickingSystem::Bubbling)
+                            .after(PickingSystem::Events),
+                    )
+                    // Exclusive systems can't be ordered against the parallel ones, so the listeners
+                    // run at the end of the stage, once the events have bubbled.
+                    .with_system(run_event_listeners.at_end()),
+            )
+            .add_system_set_to_stage(
+                CoreStage::Last,
+                SystemSet::new()
+                    .with_run_criteria(|state: Res<PickingPluginsState>| {
+                        simple_criteria(state.enable_interacting)
+                    })
+                    // At the end of the frame, so the selection changes and removals made anywhere in
+                    // it are caught the same frame.
+                    .with_system(update_selection_set.at_end())
+                    .with_system(removed_pickable_events.at_end())
+                    .with_system(record_selection_history.at_end()),
             );
     }
 }
@@ -186,7 +305,7 @@ where
                     })
                     .with_system(
                         get_initial_mesh_highlight_asset::<T>
-                            .after(PickingSystem::UpdateIntersections)
+                            .after(PickingSystem::Backend)
                             .before(PickingSystem::Highlighting),
                     )
                     .with_system(
@@ -198,13 +317,59 @@ where
     }
 }
 
+/// Adds [Marquee] (box) selection: dragging from empty space selects everything inside the
+/// rectangle.
+pub struct MarqueeSelectionPlugin;
+impl Plugin for MarqueeSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Marquee>().add_system_set_to_stage(
+            CoreStage::First,
+            SystemSet::new()
+                .with_run_criteria(|state: Res<PickingPluginsState>| {
+                    simple_criteria(state.enable_interacting)
+                })
+                .with_system(
+                    marquee_selection
+                        .label(PickingSystem::Selection)
+                        .after(PickingSystem::Focus),
+                ),
+        );
+    }
+}
+
+/// Adds [Lasso] selection: drawing a path from empty space selects everything inside it. The lasso
+/// is switched on, taking presses over empty space over from the [Marquee]; set [Lasso::enabled]
+/// to `false` to hand them back. A [Lasso] inserted before the plugin is kept as it is.
+pub struct LassoSelectionPlugin;
+impl Plugin for LassoSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<Lasso>() {
+            let mut lasso = Lasso::default();
+            lasso.enabled = true;
+            app.insert_resource(lasso);
+        }
+        app.add_system_set_to_stage(
+            CoreStage::First,
+            SystemSet::new()
+                .with_run_criteria(|state: Res<PickingPluginsState>| {
+                    simple_criteria(state.enable_interacting)
+                })
+                .with_system(
+                    lasso_selection
+                        .label(PickingSystem::Selection)
+                        .after(PickingSystem::Focus),
+                ),
+        );
+    }
+}
+
 pub struct DebugCursorPickingPlugin;
 impl Plugin for DebugCursorPickingPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_to_stage(
             CoreStage::First,
             bevy_mod_raycast::update_debug_cursor::<PickingRaycastSet>
-                .after(PickingSystem::UpdateIntersections),
+                .after(PickingSystem::Backend),
         );
     }
 }
@@ -234,8 +399,14 @@ impl Default for PickingCameraBundle {
     }
 }
 
+/// Makes a mesh pickable, hoverable, selectable and highlighted.
+///
+/// This is a breaking change from earlier versions: only entities with a [Pickable] are hovered and
+/// pressed. The bundle includes it, but entities made pickable by adding the other components one
+/// by one need a [Pickable] added as well.
 #[derive(Bundle, Default)]
 pub struct PickableBundle {
+    pub pickable: Pickable,
     pub pickable_mesh: PickableMesh,
     pub interaction: Interaction,
     pub focus_policy: FocusPolicy,
@@ -0,0 +1,195 @@
+use crate::{BubbledEvent, DragEvent, PickingEvent};
+use bevy::{
+    ecs::{event::ManualEventReader, system::BoxedSystem},
+    prelude::*,
+};
+use std::marker::PhantomData;
+
+/// Calls back when the entity it is on, or one of its descendants, receives a [PickingEvent]
+/// selected by `K`. Use the [OnClick], [OnHover], [OnSelect] and [OnDragEnd] aliases.
+#[derive(Component)]
+pub struct EventListener<K: ListenerKind> {
+    // Taken out of the component while it runs, so the callback can access the world.
+    callback: Option<Callback>,
+    _kind: PhantomData<K>,
+}
+
+/// Runs when the entity or one of its descendants is clicked.
+pub type OnClick = EventListener<Click>;
+/// Runs when a pointer starts or stops hovering the entity or one of its descendants.
+pub type OnHover = EventListener<Hovered>;
+/// Runs when the entity or one of its descendants is selected or deselected.
+pub type OnSelect = EventListener<Select>;
+/// Runs when the entity or one of its descendants stops being dragged.
+pub type OnDragEnd = EventListener<DragEnd>;
+
+impl<K: ListenerKind> EventListener<K> {
+    /// Calls `callback` with the world and the event every time the listener receives a matching
+    /// event.
+    pub fn new(
+        callback: impl FnMut(&mut World, &mut ListenerInput) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            callback: Some(Callback::Closure(Box::new(callback))),
+            _kind: PhantomData,
+        }
+    }
+    /// Runs `system` every time the listener receives a matching event, passing the event in with
+    /// an [In] parameter.
+    pub fn run<Marker>(system: impl IntoSystem<ListenerInput, (), Marker>) -> Self {
+        Self {
+            callback: Some(Callback::System {
+                system: Box::new(IntoSystem::into_system(system)),
+                initialized: false,
+            }),
+            _kind: PhantomData,
+        }
+    }
+}
+
+type ListenerFn = dyn FnMut(&mut World, &mut ListenerInput) + Send + Sync;
+
+enum Callback {
+    Closure(Box<ListenerFn>),
+    System {
+        system: BoxedSystem<ListenerInput, ()>,
+        initialized: bool,
+    },
+}
+
+impl Callback {
+    fn run(&mut self, world: &mut World, input: &mut ListenerInput) {
+        match self {
+            Callback::Closure(callback) => callback(world, input),
+            Callback::System {
+                system,
+                initialized,
+            } => {
+                if !*initialized {
+                    system.initialize(world);
+                    *initialized = true;
+                }
+                system.run(input.clone(), world);
+                system.apply_buffers(world);
+            }
+        }
+    }
+}
+
+/// The event an [EventListener] was called for.
+#[derive(Debug, Clone)]
+pub struct ListenerInput {
+    /// The entity the event happened on.
+    pub target: Entity,
+    /// The entity the listener is on: the target itself, or one of its ancestors.
+    pub listener: Entity,
+    pub event: PickingEvent,
+    propagate: bool,
+}
+
+impl ListenerInput {
+    /// Stops the event from reaching listeners further up the hierarchy.
+    pub fn stop_propagation(&mut self) {
+        self.propagate = false;
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Selects the [PickingEvent]s an [EventListener] is called for. Only [run_event_listeners] calls
+/// listeners, and only of the kinds it knows, so this is sealed: it is implemented by [Click],
+/// [Hovered], [Select] and [DragEnd].
+pub trait ListenerKind: sealed::Sealed + Send + Sync + 'static {
+    fn matches(event: &PickingEvent) -> bool;
+}
+
+/// Listens for [PickingEvent::Clicked].
+pub enum Click {}
+impl sealed::Sealed for Click {}
+impl ListenerKind for Click {
+    fn matches(event: &PickingEvent) -> bool {
+        matches!(event, PickingEvent::Clicked(_))
+    }
+}
+
+/// Listens for [PickingEvent::Hover].
+pub enum Hovered {}
+impl sealed::Sealed for Hovered {}
+impl ListenerKind for Hovered {
+    fn matches(event: &PickingEvent) -> bool {
+        matches!(event, PickingEvent::Hover(_))
+    }
+}
+
+/// Listens for [PickingEvent::Selection].
+pub enum Select {}
+impl sealed::Sealed for Select {}
+impl ListenerKind for Select {
+    fn matches(event: &PickingEvent) -> bool {
+        matches!(event, PickingEvent::Selection(_))
+    }
+}
+
+/// Listens for [DragEvent::DragEnd].
+pub enum DragEnd {}
+impl sealed::Sealed for DragEnd {}
+impl ListenerKind for DragEnd {
+    fn matches(event: &PickingEvent) -> bool {
+        matches!(event, PickingEvent::Drag(DragEvent::DragEnd { .. }))
+    }
+}
+
+/// Calls the [EventListener]s of every entity that received a [BubbledEvent] since the last run,
+/// in bubbling order, until a listener stops propagation. Runs at the end of [CoreStage::First],
+/// so listeners see the events of the same frame.
+pub fn run_event_listeners(world: &mut World, mut reader: Local<ManualEventReader<BubbledEvent>>) {
+    let events = world.resource::<Events<BubbledEvent>>();
+    let bubbled: Vec<BubbledEvent> = reader.iter(events).cloned().collect();
+
+    let mut propagate = true;
+    for BubbledEvent {
+        target,
+        listener,
+        event,
+    } in bubbled
+    {
+        // Each event bubbles up from its target, so a new event starts when the target is reached.
+        if listener == target {
+            propagate = true;
+        }
+        if !propagate {
+            continue;
+        }
+        let mut input = ListenerInput {
+            target,
+            listener,
+            event,
+            propagate: true,
+        };
+        run_listener::<Click>(world, &mut input);
+        run_listener::<Hovered>(world, &mut input);
+        run_listener::<Select>(world, &mut input);
+        run_listener::<DragEnd>(world, &mut input);
+        propagate = input.propagate;
+    }
+}
+
+fn run_listener<K: ListenerKind>(world: &mut World, input: &mut ListenerInput) {
+    if !K::matches(&input.event) {
+        return;
+    }
+    let callback = world
+        .get_mut::<EventListener<K>>(input.listener)
+        .and_then(|mut listener| listener.callback.take());
+    if let Some(mut callback) = callback {
+        callback.run(world, input);
+        // The callback may have despawned the entity or replaced the listener.
+        if let Some(mut listener) = world.get_mut::<EventListener<K>>(input.listener) {
+            if listener.callback.is_none() {
+                listener.callback = Some(callback);
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use crate::{
+    frustum::aabb_corners, selection::select_area, DragSettings, FrustumSelection, Lasso,
+    PausedForBlockers, PickingButtons, PickingCamera, PointerButton, PointerFocus, PointerId,
+    Pointers, Selection, SelectionBindings, SelectionFilter, SelectionMode, SelectionRoots,
+    SelectionScope, SelectionScopes,
+};
+use bevy::{
+    math::Rect,
+    prelude::*,
+    render::{camera::RenderTarget, primitives::Aabb},
+    utils::HashSet,
+};
+
+/// A press over empty space that starts an area selection, such as the [Marquee].
+#[derive(Debug, Clone)]
+pub(crate) struct AreaSelectionStart {
+    pub pointer: PointerId,
+    pub button: PointerButton,
+    pub camera: Entity,
+    pub target: RenderTarget,
+    /// Where the pointer is on `target` minus where it is in the camera's viewport.
+    pub viewport_offset: Vec2,
+    pub position: Vec2,
+}
+
+/// How the [Marquee] decides which entities are inside its rectangle.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MarqueeTest {
+    /// The entity's [Aabb], projected onto the screen, lies entirely inside the rectangle.
+    #[default]
+    ProjectedBounds,
+    /// The entity's [Aabb], or its origin if it has none, touches the volume of the camera's view
+    /// behind the rectangle. Only [PickableMesh](crate::PickableMesh)es and
+    /// [Pickable](crate::Pickable) entities, such as sprites, are tested.
+    Frustum,
+    /// Like [MarqueeTest::Frustum], but entities hidden behind nearer pickable meshes are left
+    /// out. Visibility is sampled with a ray every `spacing` logical pixels.
+    VisibleFrustum { spacing: f32 },
+}
+
+/// A rectangle being dragged out by a pointer to select everything inside it.
+#[derive(Debug, Clone)]
+struct ActiveMarquee {
+    start: AreaSelectionStart,
+    end: Vec2,
+    mode: SelectionMode,
+    dragging: bool,
+}
+
+/// The marquee (box) selection, started by pressing a [PickingButtons::select] button over empty
+/// space and dragging. On release, every [Selection] entity whose projected bounds lie inside the
+/// rectangle is selected, as chosen by the [SelectionBindings] chords held when the drag started.
+#[derive(Debug, Default, Resource)]
+pub struct Marquee {
+    pub test: MarqueeTest,
+    active: Option<ActiveMarquee>,
+}
+
+impl Marquee {
+    /// The rectangle being dragged, in logical pixels from the bottom-left corner of
+    /// [Marquee::target]. `None` until the pointer moves further than
+    /// [DragSettings::threshold].
+    pub fn rect(&self) -> Option<Rect> {
+        self.active
+            .as_ref()
+            .filter(|marquee| marquee.dragging)
+            .map(|marquee| Rect::from_corners(marquee.start.position, marquee.end))
+    }
+    /// The render target the marquee is drawn on.
+    pub fn target(&self) -> Option<&RenderTarget> {
+        self.active.as_ref().map(|marquee| &marquee.start.target)
+    }
+    /// The camera whose view the marquee selects from.
+    pub fn camera(&self) -> Option<Entity> {
+        self.active.as_ref().map(|marquee| marquee.start.camera)
+    }
+    pub fn mode(&self) -> Option<SelectionMode> {
+        self.active.as_ref().map(|marquee| marquee.mode)
+    }
+}
+
+/// Starts, updates and finishes the [Marquee] selection.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn marquee_selection(
+    paused: Option<Res<PausedForBlockers>>,
+    lasso: Option<Res<Lasso>>,
+    pointers: Res<Pointers>,
+    focus: Res<PointerFocus>,
+    buttons: Res<PickingButtons>,
+    bindings: Res<SelectionBindings>,
+    scopes: Res<SelectionScopes>,
+    filter: Res<SelectionFilter>,
+    roots: SelectionRoots,
+    drag_settings: Res<DragSettings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    frustum: FrustumSelection,
+    mut marquee: ResMut<Marquee>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform), With<PickingCamera>>,
+    mut selections: Query<(
+        Entity,
+        &mut Selection,
+        &GlobalTransform,
+        Option<&Aabb>,
+        Option<&ComputedVisibility>,
+        Option<&SelectionScope>,
+    )>,
+) {
+    // The lasso takes over presses over empty space while it is enabled.
+    let lasso_enabled = lasso.is_some_and(|lasso| lasso.enabled);
+    if marquee.active.is_none() && !lasso_enabled && !paused.is_some_and(|paused| paused.0) {
+        marquee.active = start_area_selection(&pointers, &focus, &buttons, &cameras).map(|start| {
+            ActiveMarquee {
+                end: start.position,
+                start,
+                mode: bindings.mode(&keyboard_input, &mouse_input),
+                dragging: false,
+            }
+        });
+    }
+
+    let active = match marquee.active.as_mut() {
+        Some(active) => active,
+        None => return,
+    };
+    let state = pointers.get(active.start.pointer);
+    if let Some(location) = state.and_then(|state| state.location_on(&active.start.target)) {
+        active.end = location.position;
+    }
+    if active.start.position.distance(active.end) >= drag_settings.threshold {
+        active.dragging = true;
+    }
+    if state.is_some_and(|state| state.pressed(active.start.button)) {
+        return;
+    }
+
+    let active = match marquee.active.take() {
+        Some(active) if active.dragging => active,
+        _ => return,
+    };
+    let (camera, camera_transform) = match cameras.get(active.start.camera) {
+        Ok((_, camera, transform)) => (camera, transform),
+        Err(_) => return,
+    };
+    let offset = active.start.viewport_offset;
+    let rect = Rect::from_corners(active.start.position - offset, active.end - offset);
+    let inside: HashSet<Entity> = match marquee.test {
+        MarqueeTest::ProjectedBounds => selections
+            .iter()
+            .filter(|(.., visibility, _)| visibility.map(|v| v.is_visible()).unwrap_or(true))
+            .filter_map(|(entity, _, transform, aabb, ..)| {
+                let corners = projected_corners(camera, camera_transform, transform, aabb)?;
+                corners
+                    .iter()
+                    .all(|corner| rect.contains(*corner))
+                    .then_some(entity)
+            })
+            .collect(),
+        MarqueeTest::Frustum => frustum
+            .entities_in_rect(active.start.camera, rect)
+            .into_iter()
+            .collect(),
+        MarqueeTest::VisibleFrustum { spacing } => frustum
+            .visible_entities_in_rect(active.start.camera, rect, spacing)
+            .into_iter()
+            .collect(),
+    };
+    select_area(
+        selections
+            .iter_mut()
+            .map(|(entity, selection, .., scope)| (entity, selection, scope)),
+        &inside,
+        active.mode,
+        &scopes,
+        &filter,
+        |entity| roots.resolve(entity),
+    );
+}
+
+/// Finds the first pointer that pressed a selection button over empty space inside a picking
+/// camera's viewport.
+pub(crate) fn start_area_selection(
+    pointers: &Pointers,
+    focus: &PointerFocus,
+    buttons: &PickingButtons,
+    cameras: &Query<(Entity, &Camera, &GlobalTransform), With<PickingCamera>>,
+) -> Option<AreaSelectionStart> {
+    pointers.iter().find_map(|(pointer, state)| {
+        let button = buttons
+            .select
+            .iter()
+            .copied()
+            .find(|button| state.just_pressed(*button))?;
+        if !focus.hovered(pointer).is_empty() {
+            return None;
+        }
+        // The topmost camera under the pointer is the one the user sees.
+        let (camera, camera_component, position) = cameras
+            .iter()
+            .filter(|(_, camera, _)| camera.is_active)
+            .filter_map(|(entity, camera, _)| {
+                Some((entity, camera, state.viewport_position(camera)?))
+            })
+            .max_by_key(|(_, camera, _)| camera.priority)?;
+        let location = state.location_on(&camera_component.target)?;
+        Some(AreaSelectionStart {
+            pointer,
+            button,
+            camera,
+            target: location.target.clone(),
+            viewport_offset: location.position - position,
+            position: location.position,
+        })
+    })
+}
+
+/// The corners of an entity's [Aabb] projected into `camera`'s viewport, or its projected origin if
+/// it has no [Aabb]. `None` if any corner is outside the camera's frustum.
+pub(crate) fn projected_corners(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    transform: &GlobalTransform,
+    aabb: Option<&Aabb>,
+) -> Option<Vec<Vec2>> {
+    let corners: Vec<Vec3> = match aabb {
+        Some(aabb) => aabb_corners(aabb, transform).to_vec(),
+        None => vec![transform.translation()],
+    };
+    corners
+        .into_iter()
+        .map(|corner| camera.world_to_viewport(camera_transform, corner))
+        .collect()
+}
@@ -1,14 +1,20 @@
-use crate::{PickingCamera, UpdatePicks};
-use bevy::{
-    prelude::*,
-    render::camera::{Camera, RenderTarget},
-};
-use bevy_mod_raycast::RaycastMethod;
+use crate::{PickingCamera, PointerId, Pointers, UpdatePicks};
+use bevy::{prelude::*, render::camera::Camera, utils::HashSet};
+use bevy_mod_raycast::{Ray3d, RaycastMethod};
 
-/// Update Screenspace ray cast sources with the current mouse position
+/// A ray cast source that follows a single non-mouse pointer through a [PickingCamera]. The mouse
+/// uses the camera's own [PickingCamera]; every other pointer gets one of these per camera. They
+/// are spawned and despawned automatically by [spawn_pointer_rays].
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PointerRay {
+    pub pointer: PointerId,
+    pub camera: Entity,
+}
+
+/// Update Screenspace ray cast sources with the current mouse position. Positions are given in the
+/// coordinates of the whole render target; the ray cast accounts for the camera's viewport.
 pub fn update_pick_source_positions(
-    touches_input: Res<Touches>,
-    mut cursor: EventReader<CursorMoved>,
+    pointers: Res<Pointers>,
     mut pick_source_query: Query<(
         &mut PickingCamera,
         Option<&mut UpdatePicks>,
@@ -16,15 +22,11 @@ pub fn update_pick_source_positions(
     )>,
 ) {
     for (mut pick_source, option_update_picks, option_camera) in &mut pick_source_query.iter_mut() {
-        let (mut update_picks, cursor_latest) = match get_inputs(
-            option_camera,
-            option_update_picks,
-            &mut cursor,
-            &touches_input,
-        ) {
-            Some(value) => value,
-            None => continue,
-        };
+        let (mut update_picks, cursor_latest, cursor_moved) =
+            match get_inputs(option_camera, option_update_picks, &pointers) {
+                Some(value) => value,
+                None => continue,
+            };
         match *update_picks {
             UpdatePicks::EveryFrame(cached_cursor_pos) => {
                 match cursor_latest {
@@ -36,10 +38,10 @@ pub fn update_pick_source_positions(
                 };
             }
             UpdatePicks::OnMouseEvent => match cursor_latest {
-                Some(cursor_moved) => {
-                    pick_source.cast_method = RaycastMethod::Screenspace(cursor_moved)
+                Some(cursor_latest) if cursor_moved => {
+                    pick_source.cast_method = RaycastMethod::Screenspace(cursor_latest)
                 }
-                None => continue,
+                _ => continue,
             },
         };
     }
@@ -48,30 +50,111 @@ pub fn update_pick_source_positions(
 fn get_inputs<'a>(
     option_camera: Option<&Camera>,
     option_update_picks: Option<Mut<'a, UpdatePicks>>,
-    cursor: &mut EventReader<CursorMoved>,
-    touches_input: &Res<Touches>,
-) -> Option<(Mut<'a, UpdatePicks>, Option<Vec2>)> {
+    pointers: &Pointers,
+) -> Option<(Mut<'a, UpdatePicks>, Option<Vec2>, bool)> {
     let camera = option_camera?;
     let update_picks = option_update_picks?;
-    let height = camera.logical_target_size()?.y;
-    let cursor_latest = match cursor.iter().last() {
-        Some(cursor_moved) => {
-            if let RenderTarget::Window(window) = camera.target {
-                if cursor_moved.id == window {
-                    Some(cursor_moved.position)
-                } else {
-                    None
-                }
-            } else {
-                None
+    let mouse = pointers.get(PointerId::Mouse)?;
+    // The mouse may be over this camera's target directly, or through a portal onto the image the
+    // camera renders to. Positions outside the camera's viewport are ignored.
+    let cursor_latest = mouse
+        .viewport_position(camera)
+        .and(mouse.location_on(&camera.target))
+        .map(|location| location.position);
+    Some((update_picks, cursor_latest, mouse.moved()))
+}
+
+/// Keeps one [PointerRay] per picking camera for every touch and custom pointer.
+///
+/// This runs at the end of the frame, so rays for touches and custom pointers that appeared this
+/// frame already exist when picking runs at the start of the next frame, in time for their press.
+/// Touches are read straight from [Touches] for the same reason.
+pub fn spawn_pointer_rays(
+    mut commands: Commands,
+    pointers: Res<Pointers>,
+    touches_input: Res<Touches>,
+    cameras: Query<Entity, (With<PickingCamera>, With<Camera>)>,
+    rays: Query<(Entity, &PointerRay)>,
+) {
+    let active_pointers: HashSet<PointerId> = touches_input
+        .iter()
+        .chain(touches_input.iter_just_released())
+        .chain(touches_input.iter_just_cancelled())
+        .map(|touch| PointerId::Touch(touch.id()))
+        .chain(pointers.iter().map(|(id, _)| id))
+        .filter(|id| *id != PointerId::Mouse)
+        .collect();
+
+    let mut existing = HashSet::new();
+    for (entity, ray) in rays.iter() {
+        if active_pointers.contains(&ray.pointer) && cameras.contains(ray.camera) {
+            existing.insert((ray.pointer, ray.camera));
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for pointer in active_pointers.iter() {
+        for camera in cameras.iter() {
+            if existing.contains(&(*pointer, camera)) {
+                continue;
             }
+            let mut source = PickingCamera::new();
+            source.cast_method = RaycastMethod::Transform;
+            commands.spawn((
+                source,
+                PointerRay {
+                    pointer: *pointer,
+                    camera,
+                },
+                TransformBundle::default(),
+            ));
         }
-        None => touches_input.iter().last().map(|touch| {
-            Vec2::new(
-                touch.position().x as f32,
-                height - touch.position().y as f32,
-            )
-        }),
-    };
-    Some((update_picks, cursor_latest))
+    }
+}
+
+/// Points every [PointerRay] from its camera through the current position of its pointer.
+pub fn update_pointer_rays(
+    pointers: Res<Pointers>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut rays: Query<(&PointerRay, &mut Transform, &mut GlobalTransform), Without<Camera>>,
+) {
+    for (ray, mut transform, mut global_transform) in rays.iter_mut() {
+        let (camera, camera_transform) = match cameras.get(ray.camera) {
+            Ok(camera) => camera,
+            Err(_) => continue,
+        };
+        let location = match pointers.get(ray.pointer).and_then(|p| {
+            p.viewport_position(camera)
+                .and(p.location_on(&camera.target))
+        }) {
+            Some(location) => location,
+            None => continue,
+        };
+        let ray = match Ray3d::from_screenspace(location.position, camera, camera_transform) {
+            Some(ray) => ray,
+            None => continue,
+        };
+        let up = if ray.direction().abs().abs_diff_eq(Vec3::Y, 1e-4) {
+            Vec3::Z
+        } else {
+            Vec3::Y
+        };
+        // Ray casts built from a transform point down its local -Z axis.
+        *transform = Transform::from_translation(ray.origin())
+            .looking_at(ray.origin() + ray.direction(), up);
+        *global_transform = GlobalTransform::from(*transform);
+    }
+}
+
+/// Returns true if `pointer` is currently inside the viewport of `camera`.
+pub(crate) fn pointer_in_viewport(
+    pointers: &Pointers,
+    pointer: PointerId,
+    camera: &Camera,
+) -> bool {
+    pointers
+        .get(pointer)
+        .and_then(|p| p.viewport_position(camera))
+        .is_some()
 }
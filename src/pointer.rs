@@ -0,0 +1,254 @@
+use bevy::{
+    prelude::*,
+    render::camera::{Camera, RenderTarget},
+    utils::{HashMap, HashSet},
+    window::WindowId,
+};
+
+/// Identifies a single pointer that can hover and press [PickableMesh](crate::PickableMesh)es.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PointerId {
+    /// The mouse cursor.
+    Mouse,
+    /// A finger on a touch screen, using the id bevy assigns to the touch.
+    Touch(u64),
+    /// A pointer driven by user code, e.g. a gamepad-controlled cursor.
+    Custom(u64),
+}
+
+/// A button a pointer can press. Touches and most custom pointers only have a primary button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PointerButton {
+    /// The left mouse button, or a finger touching the screen.
+    Primary,
+    /// The right mouse button.
+    Secondary,
+    /// The middle mouse button.
+    Middle,
+}
+
+impl PointerButton {
+    pub const ALL: [PointerButton; 3] = [
+        PointerButton::Primary,
+        PointerButton::Secondary,
+        PointerButton::Middle,
+    ];
+
+    pub fn mouse_button(&self) -> MouseButton {
+        match self {
+            PointerButton::Primary => MouseButton::Left,
+            PointerButton::Secondary => MouseButton::Right,
+            PointerButton::Middle => MouseButton::Middle,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Selects which pointer buttons drive picking interactions.
+#[derive(Debug, Clone, Resource)]
+pub struct PickingButtons {
+    /// Buttons that press entities, setting their [Interaction] to `Clicked` and showing the
+    /// pressed highlight.
+    pub press: Vec<PointerButton>,
+    /// Buttons that change the selection when clicked.
+    pub select: Vec<PointerButton>,
+}
+
+impl Default for PickingButtons {
+    fn default() -> Self {
+        Self {
+            press: vec![PointerButton::Primary],
+            select: vec![PointerButton::Primary],
+        }
+    }
+}
+
+/// Where a pointer is: the render target it is over, and its position on that target in logical
+/// pixels, measured from the bottom-left corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointerLocation {
+    pub target: RenderTarget,
+    pub position: Vec2,
+}
+
+/// The location and press state of a single pointer.
+#[derive(Debug, Clone, Default)]
+pub struct PointerState {
+    location: Option<PointerLocation>,
+    portal_locations: Vec<PointerLocation>,
+    moved: bool,
+    pressed: [bool; 3],
+    was_pressed: [bool; 3],
+    just_pressed: [bool; 3],
+    just_released: [bool; 3],
+}
+
+impl PointerState {
+    /// The current location of the pointer, or `None` if it has not been placed yet.
+    pub fn location(&self) -> Option<&PointerLocation> {
+        self.location.as_ref()
+    }
+    /// Where the pointer is on `target`, either directly or through a
+    /// [PickingPortal](crate::PickingPortal) that shows an image rendered by a camera.
+    pub fn location_on(&self, target: &RenderTarget) -> Option<&PointerLocation> {
+        self.locations().find(|location| &location.target == target)
+    }
+    /// The location of the pointer, followed by its locations inside any portals it is over.
+    pub fn locations(&self) -> impl Iterator<Item = &PointerLocation> {
+        self.location.iter().chain(self.portal_locations.iter())
+    }
+    /// The position of the pointer inside `camera`'s viewport, measured from the bottom-left corner
+    /// of the viewport, or `None` if the pointer is not over the viewport.
+    pub fn viewport_position(&self, camera: &Camera) -> Option<Vec2> {
+        let location = self.location_on(&camera.target)?;
+        let (min, max) = camera.logical_viewport_rect()?;
+        let target_size = camera.logical_target_size()?;
+        // Viewports are placed from the top-left corner of their target.
+        let position = location.position - Vec2::new(min.x, target_size.y - max.y);
+        let size = max - min;
+        (position.cmpge(Vec2::ZERO).all() && position.cmple(size).all()).then_some(position)
+    }
+    /// Whether the pointer moved since the last frame.
+    pub fn moved(&self) -> bool {
+        self.moved
+    }
+    pub fn pressed(&self, button: PointerButton) -> bool {
+        self.pressed[button.index()]
+    }
+    pub fn just_pressed(&self, button: PointerButton) -> bool {
+        self.just_pressed[button.index()]
+    }
+    pub fn just_released(&self, button: PointerButton) -> bool {
+        self.just_released[button.index()]
+    }
+    /// The buttons of this pointer that were pressed since the last frame.
+    pub fn iter_just_pressed(&self) -> impl Iterator<Item = PointerButton> + '_ {
+        PointerButton::ALL
+            .into_iter()
+            .filter(|button| self.just_pressed(*button))
+    }
+    /// The buttons of this pointer that were released since the last frame.
+    pub fn iter_just_released(&self) -> impl Iterator<Item = PointerButton> + '_ {
+        PointerButton::ALL
+            .into_iter()
+            .filter(|button| self.just_released(*button))
+    }
+}
+
+/// Tracks every pointer that is currently able to interact with pickable meshes. The mouse and
+/// touches are updated from bevy's input resources; [PointerId::Custom] pointers are driven by the
+/// user with [Pointers::set_location], [Pointers::press] and [Pointers::release].
+#[derive(Debug, Default, Resource)]
+pub struct Pointers {
+    pointers: HashMap<PointerId, PointerState>,
+}
+
+impl Pointers {
+    pub fn get(&self, id: PointerId) -> Option<&PointerState> {
+        self.pointers.get(&id)
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (PointerId, &PointerState)> {
+        self.pointers.iter().map(|(id, state)| (*id, state))
+    }
+    pub fn any_just_pressed(&self, button: PointerButton) -> bool {
+        self.pointers.values().any(|p| p.just_pressed(button))
+    }
+    pub fn any_just_released(&self, button: PointerButton) -> bool {
+        self.pointers.values().any(|p| p.just_released(button))
+    }
+    /// Moves a pointer, adding it if it does not exist yet.
+    pub fn set_location(&mut self, id: PointerId, location: PointerLocation) {
+        let state = self.pointers.entry(id).or_default();
+        if state.location.as_ref() != Some(&location) {
+            state.location = Some(location);
+            state.moved = true;
+        }
+    }
+    /// Marks a pointer button as pressed. The press is picked up the next time pointers are
+    /// updated.
+    pub fn press(&mut self, id: PointerId, button: PointerButton) {
+        self.pointers.entry(id).or_default().pressed[button.index()] = true;
+    }
+    /// Marks a pointer button as released. The release is picked up the next time pointers are
+    /// updated.
+    pub fn release(&mut self, id: PointerId, button: PointerButton) {
+        if let Some(state) = self.pointers.get_mut(&id) {
+            state.pressed[button.index()] = false;
+        }
+    }
+    pub fn remove(&mut self, id: PointerId) -> Option<PointerState> {
+        self.pointers.remove(&id)
+    }
+    pub(crate) fn set_portal_locations(&mut self, id: PointerId, locations: Vec<PointerLocation>) {
+        if let Some(state) = self.pointers.get_mut(&id) {
+            state.portal_locations = locations;
+        }
+    }
+}
+
+/// Updates the mouse and touch pointers from bevy's input, and computes the press edges of every
+/// pointer. Touch pointers are removed once their touch is no longer reported.
+pub fn update_pointers(
+    mut pointers: ResMut<Pointers>,
+    mut cursor: EventReader<CursorMoved>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    touches_input: Res<Touches>,
+    windows: Res<Windows>,
+) {
+    for state in pointers.pointers.values_mut() {
+        state.moved = false;
+    }
+
+    if let Some(cursor_moved) = cursor.iter().last() {
+        pointers.set_location(
+            PointerId::Mouse,
+            PointerLocation {
+                target: RenderTarget::Window(cursor_moved.id),
+                position: cursor_moved.position,
+            },
+        );
+    }
+    let mouse = pointers.pointers.entry(PointerId::Mouse).or_default();
+    for button in PointerButton::ALL {
+        mouse.pressed[button.index()] = mouse_button_input.pressed(button.mouse_button());
+    }
+
+    // Touch positions are reported from the top-left corner of the primary window.
+    let height = windows.get_primary().map(|window| window.height());
+    let mut active_touches = HashSet::new();
+    let released = touches_input
+        .iter_just_released()
+        .chain(touches_input.iter_just_cancelled());
+    for (touch, pressed) in touches_input
+        .iter()
+        .map(|touch| (touch, true))
+        .chain(released.map(|touch| (touch, false)))
+    {
+        let id = PointerId::Touch(touch.id());
+        active_touches.insert(id);
+        if let Some(height) = height {
+            pointers.set_location(
+                id,
+                PointerLocation {
+                    target: RenderTarget::Window(WindowId::primary()),
+                    position: Vec2::new(touch.position().x, height - touch.position().y),
+                },
+            );
+        }
+        pointers.pointers.entry(id).or_default().pressed[PointerButton::Primary.index()] = pressed;
+    }
+    pointers
+        .pointers
+        .retain(|id, _| !matches!(id, PointerId::Touch(_)) || active_touches.contains(id));
+
+    for state in pointers.pointers.values_mut() {
+        for i in 0..PointerButton::ALL.len() {
+            state.just_pressed[i] = state.pressed[i] && !state.was_pressed[i];
+            state.just_released[i] = !state.pressed[i] && state.was_pressed[i];
+        }
+        state.was_pressed = state.pressed;
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{PickingHits, PointerFocus, PointerId, PointerLocation, Pointers};
+use bevy::{
+    prelude::*,
+    render::{camera::RenderTarget, mesh::VertexAttributeValues},
+    sprite::Mesh2dHandle,
+};
+
+/// How a [PickingPortal] maps a pointer onto its image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PortalMapping {
+    /// The image fills the rect of this entity's UI [Node], e.g. an `ImageBundle` showing it.
+    #[default]
+    UiNode,
+    /// The image is textured onto this entity's mesh with its first UV channel, e.g. an in-world
+    /// monitor. Pointers are mapped through the UV coordinates where they hit the mesh.
+    MeshUv,
+}
+
+/// Forwards pointers that are over this entity into `image`, so a
+/// [PickingCamera](crate::PickingCamera) that renders to that image can pick with them, as if the
+/// pointer were over the image itself. What is hit on the image is on top of this entity, whatever
+/// the priorities of the cameras involved.
+///
+/// Mesh portals are mapped with the hits from the previous frame, so picking through them lags the
+/// pointer by one frame. Pointers can also be placed on an image directly, by giving a
+/// [PointerId::Custom] pointer a [PointerLocation] with a [RenderTarget::Image] target.
+#[derive(Component, Debug, Clone, Default)]
+pub struct PickingPortal {
+    pub image: Handle<Image>,
+    pub mapping: PortalMapping,
+}
+
+impl PickingPortal {
+    pub fn ui_node(image: Handle<Image>) -> Self {
+        PickingPortal {
+            image,
+            mapping: PortalMapping::UiNode,
+        }
+    }
+    pub fn mesh_uv(image: Handle<Image>) -> Self {
+        PickingPortal {
+            image,
+            mapping: PortalMapping::MeshUv,
+        }
+    }
+}
+
+/// Computes where each pointer is inside the images shown by [PickingPortal]s.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn update_portal_locations(
+    mut pointers: ResMut<Pointers>,
+    focus: Option<Res<PointerFocus>>,
+    windows: Res<Windows>,
+    images: Res<Assets<Image>>,
+    meshes: Res<Assets<Mesh>>,
+    portals: Query<(
+        Entity,
+        &PickingPortal,
+        &GlobalTransform,
+        Option<&Node>,
+        Option<&Handle<Mesh>>,
+        Option<&Mesh2dHandle>,
+    )>,
+    hits: Res<PickingHits>,
+) {
+    let pointer_ids: Vec<PointerId> = pointers.iter().map(|(id, _)| id).collect();
+    for pointer in pointer_ids {
+        let location = pointers.get(pointer).and_then(|p| p.location()).cloned();
+        let mut portal_locations = Vec::new();
+        for (entity, portal, transform, node, mesh, mesh_2d) in portals.iter() {
+            let image_size = match images.get(&portal.image) {
+                Some(image) => image.size(),
+                None => continue,
+            };
+            let uv = match portal.mapping {
+                PortalMapping::UiNode => node
+                    .zip(location.as_ref())
+                    .and_then(|(node, location)| ui_node_uv(node, transform, location, &windows)),
+                PortalMapping::MeshUv => {
+                    let hovered = focus
+                        .as_ref()
+                        .map(|focus| focus.hovered(pointer).contains(&entity))
+                        .unwrap_or(true);
+                    let mesh = mesh
+                        .or_else(|| mesh_2d.map(|m| &m.0))
+                        .and_then(|handle| meshes.get(handle));
+                    match (hovered, mesh) {
+                        (true, Some(mesh)) => hit_position(&hits, pointer, entity)
+                            .and_then(|hit| mesh_uv(mesh, transform, hit)),
+                        _ => None,
+                    }
+                }
+            };
+            if let Some(uv) = uv {
+                // UVs grow downwards, pointer positions grow upwards from the bottom-left corner.
+                portal_locations.push(PointerLocation {
+                    target: RenderTarget::Image(portal.image.clone()),
+                    position: Vec2::new(uv.x, 1.0 - uv.y) * image_size,
+                });
+            }
+        }
+        pointers.set_portal_locations(pointer, portal_locations);
+    }
+}
+
+/// The UV coordinates of a window pointer inside a UI node, if it is over the node.
+fn ui_node_uv(
+    node: &Node,
+    transform: &GlobalTransform,
+    location: &PointerLocation,
+    windows: &Windows,
+) -> Option<Vec2> {
+    let window = match location.target {
+        RenderTarget::Window(id) => windows.get(id)?,
+        RenderTarget::Image(_) => return None,
+    };
+    // UI node positions are measured from the top-left corner of the window.
+    let cursor = Vec2::new(location.position.x, window.height() - location.position.y);
+    let size = node.size();
+    if size.x <= 0.0 || size.y <= 0.0 {
+        return None;
+    }
+    let min = transform.translation().truncate() - size / 2.0;
+    let uv = (cursor - min) / size;
+    (uv.cmpge(Vec2::ZERO).all() && uv.cmple(Vec2::ONE).all()).then_some(uv)
+}
+
+/// The world position where `pointer` last hit `entity`.
+fn hit_position(hits: &PickingHits, pointer: PointerId, entity: Entity) -> Option<Vec3> {
+    hits.iter_pointer(pointer)
+        .filter(|hit| hit.hit.entity == entity)
+        .min_by(|a, b| a.depth.total_cmp(&b.depth))
+        .map(|hit| hit.hit.position)
+}
+
+/// Interpolates the first UV channel of `mesh` at the world position `hit` on its surface.
+fn mesh_uv(mesh: &Mesh, transform: &GlobalTransform, hit: Vec3) -> Option<Vec2> {
+    let hit = transform.compute_matrix().inverse().transform_point3(hit);
+    let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+    let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0)? {
+        VertexAttributeValues::Float32x2(uvs) => uvs,
+        _ => return None,
+    };
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+
+    // Find the triangle the hit lies on; the closest one wins where triangles overlap.
+    let mut closest: Option<(f32, Vec2)> = None;
+    for triangle in indices.chunks_exact(3) {
+        let vertex = |i: usize| Some((Vec3::from(*positions.get(i)?), Vec2::from(*uvs.get(i)?)));
+        let (a, b, c) = match (
+            vertex(triangle[0]),
+            vertex(triangle[1]),
+            vertex(triangle[2]),
+        ) {
+            (Some(a), Some(b), Some(c)) => (a, b, c),
+            _ => continue,
+        };
+        let weights = match barycentric(hit, a.0, b.0, c.0) {
+            Some(weights) if weights.min_element() >= -1e-4 => weights,
+            _ => continue,
+        };
+        let on_plane = a.0 * weights.x + b.0 * weights.y + c.0 * weights.z;
+        let distance = on_plane.distance(hit);
+        if closest
+            .map(|(closest, _)| distance < closest)
+            .unwrap_or(true)
+        {
+            let uv = a.1 * weights.x + b.1 * weights.y + c.1 * weights.z;
+            closest = Some((distance, uv));
+        }
+    }
+    closest.map(|(_, uv)| uv)
+}
+
+/// The barycentric weights of `point` projected onto the plane of the triangle `a`, `b`, `c`.
+fn barycentric(point: Vec3, a: Vec3, b: Vec3, c: Vec3) -> Option<Vec3> {
+    let (v0, v1, v2) = (b - a, c - a, point - a);
+    let (d00, d01, d11) = (v0.dot(v0), v0.dot(v1), v1.dot(v1));
+    let (d20, d21) = (v2.dot(v0), v2.dot(v1));
+    let denominator = d00 * d11 - d01 * d01;
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+    let v = (d11 * d20 - d01 * d21) / denominator;
+    let w = (d00 * d21 - d01 * d20) / denominator;
+    Some(Vec3::new(1.0 - v - w, v, w))
+}
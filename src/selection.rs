@@ -1,5 +1,13 @@
-use crate::PausedForBlockers;
-use bevy::prelude::*;
+use crate::{PausedForBlockers, PickingButtons, PointerFocus, PointerHit, Pointers};
+use bevy::{
+    ecs::{
+        system::{Command, SystemParam},
+        world::EntityRef,
+    },
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use std::borrow::Cow;
 
 /// Tracks the current selection state to be used with change tracking in the events system.
 /// Entities with [Selection] will have selection state managed.
@@ -22,64 +30,548 @@ impl Selection {
     }
 }
 
+/// How a marquee or other area selection changes the current selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMode {
+    /// Select the entities in the area, and deselect everything else.
+    #[default]
+    Replace,
+    /// Select the entities in the area, keeping the current selection.
+    Add,
+    /// Deselect the entities in the area.
+    Subtract,
+}
+
+/// Keys and mouse buttons that are held together to trigger a selection action. A chord with
+/// nothing in it never triggers.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Chord {
+    pub keys: Vec<KeyCode>,
+    pub buttons: Vec<MouseButton>,
+}
+
+impl Chord {
+    pub fn new(keys: impl IntoIterator<Item = KeyCode>) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+            buttons: Vec::new(),
+        }
+    }
+    pub fn with_button(mut self, button: MouseButton) -> Self {
+        self.buttons.push(button);
+        self
+    }
+    /// Whether everything in the chord is held.
+    pub fn pressed(&self, keys: &Input<KeyCode>, buttons: &Input<MouseButton>) -> bool {
+        !(self.keys.is_empty() && self.buttons.is_empty())
+            && self.keys.iter().all(|key| keys.pressed(*key))
+            && self.buttons.iter().all(|button| buttons.pressed(*button))
+    }
+    /// Whether everything in the chord is held, and the last of it was pressed this frame.
+    pub fn just_pressed(&self, keys: &Input<KeyCode>, buttons: &Input<MouseButton>) -> bool {
+        self.pressed(keys, buttons)
+            && (keys.any_just_pressed(self.keys.iter().copied())
+                || buttons.any_just_pressed(self.buttons.iter().copied()))
+    }
+}
+
+/// The [Chord]s that trigger each selection action. An action triggers when any of its chords is
+/// pressed.
+///
+/// `toggle`, `add` and `subtract` are held while clicking or drawing an area selection to change
+/// how it affects the current selection, and keep clicks in empty space from deselecting the
+/// default scope. `select_all`, `deselect_all` and `invert` run once when their chord is pressed.
+#[derive(Debug, Clone, Resource)]
+pub struct SelectionBindings {
+    pub toggle: Vec<Chord>,
+    pub add: Vec<Chord>,
+    pub subtract: Vec<Chord>,
+    pub select_all: Vec<Chord>,
+    pub deselect_all: Vec<Chord>,
+    pub invert: Vec<Chord>,
+}
+
+impl Default for SelectionBindings {
+    fn default() -> Self {
+        // Control on Windows and Linux, Command on macOS.
+        let command = [
+            KeyCode::LControl,
+            KeyCode::RControl,
+            KeyCode::LWin,
+            KeyCode::RWin,
+        ];
+        let with_command = |key: KeyCode| -> Vec<Chord> {
+            command
+                .iter()
+                .map(|modifier| Chord::new([*modifier, key]))
+                .collect()
+        };
+        Self {
+            toggle: command.iter().map(|key| Chord::new([*key])).collect(),
+            add: vec![Chord::new([KeyCode::LShift]), Chord::new([KeyCode::RShift])],
+            subtract: vec![Chord::new([KeyCode::LAlt]), Chord::new([KeyCode::RAlt])],
+            select_all: with_command(KeyCode::A),
+            deselect_all: [Chord::new([KeyCode::Escape])]
+                .into_iter()
+                .chain(with_command(KeyCode::D))
+                .collect(),
+            invert: with_command(KeyCode::I),
+        }
+    }
+}
+
+impl SelectionBindings {
+    /// The selection mode picked by the `add` and `subtract` chords currently held.
+    pub fn mode(&self, keys: &Input<KeyCode>, buttons: &Input<MouseButton>) -> SelectionMode {
+        if any_pressed(&self.subtract, keys, buttons) {
+            SelectionMode::Subtract
+        } else if any_pressed(&self.add, keys, buttons) {
+            SelectionMode::Add
+        } else {
+            SelectionMode::Replace
+        }
+    }
+    /// Whether a `toggle` chord is held.
+    pub fn toggling(&self, keys: &Input<KeyCode>, buttons: &Input<MouseButton>) -> bool {
+        any_pressed(&self.toggle, keys, buttons)
+    }
+}
+
+fn any_pressed(chords: &[Chord], keys: &Input<KeyCode>, buttons: &Input<MouseButton>) -> bool {
+    chords.iter().any(|chord| chord.pressed(keys, buttons))
+}
+
+fn any_just_pressed(chords: &[Chord], keys: &Input<KeyCode>, buttons: &Input<MouseButton>) -> bool {
+    chords.iter().any(|chord| chord.just_pressed(keys, buttons))
+}
+
+/// Puts an entity into a named selection group. Selecting inside a scope only changes the selection
+/// of entities in the same scope, so several independent selections, such as a unit roster and a
+/// map, can exist at once. Entities without a scope share the default scope, which is the only
+/// scope cleared by clicking in empty space.
+///
+/// How each scope selects is set with [SelectionScopes].
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
+pub struct SelectionScope(pub Cow<'static, str>);
+
+impl SelectionScope {
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self(name.into())
+    }
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for SelectionScope {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+/// How many entities of a [SelectionScope] can be selected at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScopeMode {
+    /// Any number of entities: clicking replaces the selection, the [SelectionBindings] change
+    /// how clicks and area selections combine with it, and select-all and invert apply.
+    #[default]
+    Multi,
+    /// At most one entity. Clicking selects only the clicked entity, and the `toggle` and
+    /// `subtract` bindings deselect it. Area selections, select-all and invert leave the scope
+    /// alone.
+    Single,
+    /// Exactly one entity once anything has been selected, like a group of radio buttons. The
+    /// selected entity can only be deselected by selecting another one.
+    Radio,
+}
+
+/// The [ScopeMode] of each [SelectionScope]. Scopes that have not been given a mode, including the
+/// default scope, use [ScopeMode::Multi].
+#[derive(Debug, Clone, Default, Resource)]
+pub struct SelectionScopes {
+    modes: HashMap<Cow<'static, str>, ScopeMode>,
+}
+
+impl SelectionScopes {
+    pub fn set_mode(&mut self, scope: impl Into<Cow<'static, str>>, mode: ScopeMode) -> &mut Self {
+        self.modes.insert(scope.into(), mode);
+        self
+    }
+    /// The mode of `scope`, where `None` is the default scope.
+    pub fn mode(&self, scope: Option<&SelectionScope>) -> ScopeMode {
+        self.modes
+            .get(scope_name(scope))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// The name of an entity's scope, where entities without a [SelectionScope] are in the default
+/// scope, named `""`.
+fn scope_name(scope: Option<&SelectionScope>) -> &str {
+    scope.map(SelectionScope::name).unwrap_or_default()
+}
+
+/// Applies an area selection of the `inside` entities to every [Selection], only touching the
+/// components whose state changes. Only [ScopeMode::Multi] scopes take part, and of those only the
+/// default scope and the scopes with entities inside the area, so an area drawn over the map does
+/// not clear a selection in the UI. Entities the [SelectionFilter] rejects count as outside.
+///
+/// `resolve` maps entities to the [SelectionRoot] they belong to, so an area around a root's
+/// meshes selects the root, and descendants of a root are never selected themselves.
+pub(crate) fn select_area<'a>(
+    selections: impl Iterator<Item = (Entity, Mut<'a, Selection>, Option<&'a SelectionScope>)>,
+    inside: &HashSet<Entity>,
+    mode: SelectionMode,
+    scopes: &SelectionScopes,
+    filter: &SelectionFilter,
+    resolve: impl Fn(Entity) -> Entity,
+) {
+    let inside: HashSet<Entity> = inside
+        .iter()
+        .map(|entity| resolve(*entity))
+        .filter(|entity| filter.allows(*entity))
+        .collect();
+    let selections: Vec<_> = selections
+        .filter(|(entity, ..)| resolve(*entity) == *entity)
+        .filter(|(.., scope)| scopes.mode(*scope) == ScopeMode::Multi)
+        .collect();
+    let touched: HashSet<&str> = selections
+        .iter()
+        .filter(|(entity, ..)| inside.contains(entity))
+        .map(|(.., scope)| scope_name(*scope))
+        .chain([""])
+        .collect();
+    for (entity, mut selection, scope) in selections {
+        if !touched.contains(scope_name(scope)) {
+            continue;
+        }
+        let is_inside = inside.contains(&entity);
+        let selected = match mode {
+            SelectionMode::Replace => is_inside,
+            SelectionMode::Add => selection.selected || is_inside,
+            SelectionMode::Subtract => selection.selected && !is_inside,
+        };
+        if selection.selected != selected {
+            selection.selected = selected;
+        }
+    }
+}
+
+/// Where the pointers clicked the entities whose selection a click changed this frame, recorded by
+/// [mesh_selection]. Clicks on the meshes of a [SelectionRoot] are recorded for the root.
+#[derive(Debug, Clone, Default, Resource)]
+pub struct SelectionHits {
+    hits: HashMap<Entity, PointerHit>,
+}
+
+impl SelectionHits {
+    /// The hit of the click that selected or deselected `entity` this frame, if a click did.
+    pub fn get(&self, entity: Entity) -> Option<&PointerHit> {
+        self.hits.get(&entity)
+    }
+}
+
+/// The selected entities, in the order they were selected. The most recently selected entity is
+/// the primary (active) one, e.g. the one an editor shows gizmos and properties for.
+///
+/// Kept in sync with the [Selection] components by [update_selection_set], however they changed,
+/// and by [removed_pickable_events](crate::removed_pickable_events), however they went away.
+#[derive(Debug, Clone, Default, Resource)]
+pub struct SelectionSet {
+    order: Vec<Entity>,
+}
+
+impl SelectionSet {
+    /// The most recently selected entity that is still selected.
+    pub fn primary(&self) -> Option<Entity> {
+        self.order.last().copied()
+    }
+    /// Makes an already selected entity the primary one. Returns `false` if it is not selected.
+    pub fn set_primary(&mut self, entity: Entity) -> bool {
+        match self.order.iter().position(|e| *e == entity) {
+            Some(index) => {
+                self.order.remove(index);
+                self.order.push(entity);
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.order.contains(&entity)
+    }
+    /// The selected entities, oldest first. Reverse it to start from the primary entity.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Entity> + ExactSizeIterator + '_ {
+        self.order.iter().copied()
+    }
+    /// The selected entities other than the primary one, oldest first.
+    pub fn secondary(&self) -> impl DoubleEndedIterator<Item = Entity> + ExactSizeIterator + '_ {
+        let len = self.order.len().saturating_sub(1);
+        self.order[..len].iter().copied()
+    }
+    pub fn as_slice(&self) -> &[Entity] {
+        &self.order
+    }
+    /// Forgets an entity that can no longer be selected. Returns `false` if it was not selected.
+    pub(crate) fn remove(&mut self, entity: Entity) -> bool {
+        let len = self.order.len();
+        self.order.retain(|e| *e != entity);
+        self.order.len() != len
+    }
+}
+
+impl<'a> IntoIterator for &'a SelectionSet {
+    type Item = Entity;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, Entity>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.order.iter().copied()
+    }
+}
+
+/// Updates the [SelectionSet] from the [Selection] components that changed since the last run.
+/// Entities selected in the same frame are added in no particular order. It runs after clicks in
+/// [CoreStage::First], and again at the end of [CoreStage::Last] for the changes made later in the
+/// frame, so the set never lags a frame behind. Entities that were despawned or lost their
+/// [Selection] are dropped by [removed_pickable_events](crate::removed_pickable_events).
+pub fn update_selection_set(
+    mut selection_set: ResMut<SelectionSet>,
+    changed: Query<(Entity, &Selection), Changed<Selection>>,
+) {
+    for (entity, selection) in changed.iter() {
+        let listed = selection_set.contains(entity);
+        if selection.selected && !listed {
+            selection_set.order.push(entity);
+        } else if !selection.selected && listed {
+            selection_set.order.retain(|e| *e != entity);
+        }
+    }
+}
+
 /// Marker struct used to mark pickable entities for which you don't want to trigger a deselection event when picked. This is useful for gizmos or other pickable UI entities.
 #[derive(Component, Debug, Copy, Clone, Reflect, Default)]
 #[reflect(Component)]
 pub struct NoDeselect;
 
+/// Makes clicks on the entity's descendants select the entity instead, e.g. so clicking a wheel
+/// selects the whole car it belongs to. The entity needs a [Selection], and every pickable mesh
+/// under it is highlighted together: hovering or pressing one highlights them all, and they all
+/// show the entity's selection. Clicks, area selections and [SelectionCommands] resolve to the
+/// nearest root above the mesh, and select-all and invert skip the descendants, so a descendant's
+/// own [Selection], e.g. from a [PickableBundle](crate::PickableBundle), is never selected.
+#[derive(Component, Debug, Copy, Clone, Reflect, Default)]
+#[reflect(Component)]
+pub struct SelectionRoot;
+
+/// Finds the [SelectionRoot] entities belong to.
+#[derive(SystemParam)]
+pub struct SelectionRoots<'w, 's> {
+    parents: Query<'w, 's, &'static Parent>,
+    roots: Query<'w, 's, (), With<SelectionRoot>>,
+}
+
+impl<'w, 's> SelectionRoots<'w, 's> {
+    /// The nearest [SelectionRoot] at or above `entity` in the hierarchy.
+    pub fn root_of(&self, entity: Entity) -> Option<Entity> {
+        find_root(
+            entity,
+            |entity| self.parents.get(entity).ok().map(Parent::get),
+            |entity| self.roots.contains(entity),
+        )
+    }
+    /// The entity that selecting `entity` selects: its root, or the entity itself.
+    pub fn resolve(&self, entity: Entity) -> Entity {
+        self.root_of(entity).unwrap_or(entity)
+    }
+}
+
+fn find_root(
+    entity: Entity,
+    parent: impl Fn(Entity) -> Option<Entity>,
+    is_root: impl Fn(Entity) -> bool,
+) -> Option<Entity> {
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+        if is_root(entity) {
+            return Some(entity);
+        }
+        current = parent(entity);
+    }
+    None
+}
+
+/// Keeps an entity from being selected, while it can still be hovered, pressed and clicked.
+/// Clicking it does not change the selection, as with [NoDeselect], and if it is already selected
+/// it can still be deselected.
+#[derive(Component, Debug, Copy, Clone, Reflect, Default)]
+#[reflect(Component)]
+pub struct SelectionLock;
+
+type SelectionPredicate = dyn Fn(EntityRef) -> bool + Send + Sync;
+
+/// Decides which [Selection] entities can be selected, on top of [SelectionLock]. The predicate
+/// is called with each entity once a frame, at the start of [CoreStage::First], so it sees the
+/// world as the previous frame left it. Its verdict applies to clicks, the [SelectionBindings],
+/// area selections and [SelectionCommands] alike until the next frame, except that a command that
+/// targets one entity, such as [SelectionCommands::select], calls the predicate with that entity
+/// again when it runs.
+#[derive(Default, Resource)]
+pub struct SelectionFilter {
+    predicate: Option<Box<SelectionPredicate>>,
+    /// The entities that cannot be selected, as of the last update.
+    rejected: HashSet<Entity>,
+}
+
+impl SelectionFilter {
+    /// Only lets entities that `predicate` returns `true` for be selected.
+    pub fn new(predicate: impl Fn(EntityRef) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            predicate: Some(Box::new(predicate)),
+            rejected: HashSet::new(),
+        }
+    }
+    pub fn set_predicate(&mut self, predicate: impl Fn(EntityRef) -> bool + Send + Sync + 'static) {
+        self.predicate = Some(Box::new(predicate));
+    }
+    /// Removes the predicate, leaving only [SelectionLock] to keep entities from being selected.
+    pub fn clear_predicate(&mut self) {
+        self.predicate = None;
+    }
+    /// Whether `entity` can be selected, as of the last update.
+    pub fn allows(&self, entity: Entity) -> bool {
+        !self.rejected.contains(&entity)
+    }
+    fn update(&mut self, world: &mut World) {
+        let entities: Vec<(Entity, bool)> = world
+            .query_filtered::<(Entity, Option<&SelectionLock>), With<Selection>>()
+            .iter(world)
+            .map(|(entity, lock)| (entity, lock.is_some()))
+            .collect();
+        self.rejected.clear();
+        for (entity, locked) in entities {
+            let allowed = match &self.predicate {
+                Some(predicate) => predicate(world.entity(entity)),
+                None => true,
+            };
+            if locked || !allowed {
+                self.rejected.insert(entity);
+            }
+        }
+    }
+    /// Runs the filter over `entity` alone.
+    fn update_entity(&mut self, world: &World, entity: Entity) {
+        let allowed = match world.get_entity(entity) {
+            Some(entity_ref) => {
+                !entity_ref.contains::<SelectionLock>()
+                    && match &self.predicate {
+                        Some(predicate) => predicate(entity_ref),
+                        None => true,
+                    }
+            }
+            None => true,
+        };
+        if allowed {
+            self.rejected.remove(&entity);
+        } else {
+            self.rejected.insert(entity);
+        }
+    }
+}
+
+/// Runs the [SelectionFilter] over every [Selection] entity. This is an exclusive system, so it
+/// is added at the start of [CoreStage::First] rather than ordered among the picking systems.
+pub fn update_selection_filter(world: &mut World) {
+    if world.contains_resource::<SelectionFilter>() {
+        world.resource_scope(|world, mut filter: Mut<SelectionFilter>| filter.update(world));
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn mesh_selection(
     paused: Option<Res<PausedForBlockers>>,
-    mouse_button_input: Res<Input<MouseButton>>,
-    touches_input: Res<Touches>,
+    pointers: Res<Pointers>,
+    focus: Res<PointerFocus>,
+    buttons: Res<PickingButtons>,
+    bindings: Res<SelectionBindings>,
+    scopes: Res<SelectionScopes>,
+    filter: Res<SelectionFilter>,
+    roots: SelectionRoots,
+    mut selection_hits: ResMut<SelectionHits>,
     keyboard_input: Res<Input<KeyCode>>,
-    query_changed: Query<&Interaction, (Changed<Interaction>, Without<NoDeselect>)>,
-    mut query_all: Query<(&mut Selection, &Interaction)>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut query_all: Query<(Entity, &mut Selection, Option<&SelectionScope>)>,
     node_query: Query<&Interaction, With<Node>>,
-    no_deselect_query: Query<&Interaction, With<NoDeselect>>,
+    clicked_query: Query<(Option<&SelectionScope>, Option<&NoDeselect>)>,
 ) {
+    selection_hits.hits.clear();
     if let Some(paused) = paused {
         if paused.0 {
             return;
         }
     }
 
-    // Check if something has been clicked on
-    let mut new_selection = false;
-    for interaction in query_changed.iter() {
-        if *interaction == Interaction::Clicked {
-            new_selection = true;
+    // Check if something has been clicked on with one of the selection buttons, keeping the
+    // topmost entities first.
+    let mut pointer_clicked = false;
+    let mut clicked = Vec::new();
+    let mut clicked_hits = HashMap::new();
+    for (pointer, state) in pointers.iter() {
+        if buttons
+            .select
+            .iter()
+            .any(|button| state.just_pressed(*button))
+        {
+            pointer_clicked = true;
+            for hovered in focus.hovered(pointer).iter() {
+                let entity = roots.resolve(*hovered);
+                if !clicked.contains(&entity) {
+                    clicked.push(entity);
+                }
+                if let Some(hit) = focus.hit(pointer, *hovered) {
+                    clicked_hits.entry(entity).or_insert(*hit);
+                }
+            }
         }
     }
+    let (keys, mouse) = (keyboard_input.as_ref(), mouse_input.as_ref());
+    let toggle = bindings.toggling(keys, mouse);
+    let mode = bindings.mode(keys, mouse);
 
-    if keyboard_input.pressed(KeyCode::LControl) && keyboard_input.pressed(KeyCode::A) {
-        // The user has hit ctrl+a, select all the things!
-        query_all.for_each_mut(|(mut selection, _)| {
-            if !selection.selected {
-                selection.selected = true;
-            }
-        });
-    } else if new_selection {
+    let action = if any_just_pressed(&bindings.select_all, keys, mouse) {
+        SelectionAction::SelectAll
+    } else if any_just_pressed(&bindings.deselect_all, keys, mouse) {
+        SelectionAction::DeselectAll
+    } else if any_just_pressed(&bindings.invert, keys, mouse) {
+        SelectionAction::Invert
+    } else if !clicked.is_empty() {
         // Some pickable mesh has been clicked on - figure out what to select or deselect
-        for (mut selection, interaction) in &mut query_all.iter_mut() {
-            if selection.selected
-                && *interaction != Interaction::Clicked
-                && !keyboard_input.pressed(KeyCode::LControl)
-            {
-                // In this case, the entity is currently marked as selected, but it was not clicked
-                // on (interaction), and lctrl was not being held, so it should be deselected.
-                selection.selected = false;
-            } else if *interaction == Interaction::Clicked
-                && keyboard_input.pressed(KeyCode::LControl)
-            {
-                selection.selected = !selection.selected
-            } else if !selection.selected && *interaction == Interaction::Clicked {
-                selection.selected = true;
-            }
+        selection_hits.hits = clicked_hits;
+        SelectionAction::Click {
+            clicked: clicked
+                .into_iter()
+                .map(|entity| {
+                    let (scope, no_deselect) = clicked_query.get(entity).unwrap_or_default();
+                    ClickedEntity {
+                        entity,
+                        scope: scope.cloned(),
+                        no_deselect: no_deselect.is_some() || !filter.allows(entity),
+                    }
+                })
+                .collect(),
+            toggle,
+            mode,
         }
-    } else if !keyboard_input.pressed(KeyCode::LControl) {
-        // This branch deselects everything if the user clicks, in empty space. Deselection is not
-        // run if the UI or an item tagged with `NoDeselect` was clicked on.
+    } else if pointer_clicked && !toggle && mode == SelectionMode::Replace {
+        // This branch deselects the default scope if the user clicks in empty space. Deselection
+        // is not run if the UI or an item tagged with `NoDeselect` was clicked on.
         let mut ui_not_clicked = true;
         for interaction in node_query.iter() {
             // Check if anything in the UI is being interacted with
@@ -87,20 +579,246 @@ pub fn mesh_selection(
                 ui_not_clicked = false;
             }
         }
-        let mut no_deselect_not_clicked = true;
-        for interaction in no_deselect_query.iter() {
-            if *interaction == Interaction::Clicked {
-                no_deselect_not_clicked = false;
-            }
+        if !ui_not_clicked {
+            return;
         }
-        let mouse_clicked = mouse_button_input.just_pressed(MouseButton::Left)
-            || touches_input.iter_just_pressed().next().is_some();
-        if mouse_clicked && ui_not_clicked && no_deselect_not_clicked {
-            for (mut selection, _interaction) in &mut query_all.iter_mut() {
-                if selection.selected {
-                    selection.selected = false;
+        SelectionAction::ClickNothing
+    } else {
+        return;
+    };
+    apply_selection_action(query_all.iter_mut(), &action, &scopes, &filter, |entity| {
+        roots.resolve(entity)
+    });
+}
+
+/// An entity clicked by a pointer or a [SelectionCommands] method.
+#[derive(Debug, Clone)]
+pub(crate) struct ClickedEntity {
+    pub entity: Entity,
+    pub scope: Option<SelectionScope>,
+    pub no_deselect: bool,
+}
+
+/// A change to the selection, shared by pointers, the [SelectionBindings] and
+/// [SelectionCommands] so they all behave the same.
+#[derive(Debug, Clone)]
+pub(crate) enum SelectionAction {
+    /// Clicking entities, topmost first. Only the scopes of the clicked entities change, and
+    /// clicking only [NoDeselect] entities changes nothing.
+    Click {
+        clicked: Vec<ClickedEntity>,
+        toggle: bool,
+        mode: SelectionMode,
+    },
+    /// Selects everything in [ScopeMode::Multi] scopes.
+    SelectAll,
+    /// Deselects everything outside [ScopeMode::Radio] scopes.
+    DeselectAll,
+    /// Clicking in empty space, which deselects the default scope unless it is a
+    /// [ScopeMode::Radio] scope. Named scopes are left alone, as with area selections.
+    ClickNothing,
+    /// Flips the selection of everything in [ScopeMode::Multi] scopes.
+    Invert,
+}
+
+/// Applies `action` to every [Selection], only touching the components whose state changes.
+/// Entities the [SelectionFilter] rejects are never selected, and descendants of a
+/// [SelectionRoot], which `resolve` maps to their root, are left alone.
+pub(crate) fn apply_selection_action<'a>(
+    selections: impl Iterator<Item = (Entity, Mut<'a, Selection>, Option<&'a SelectionScope>)>,
+    action: &SelectionAction,
+    scopes: &SelectionScopes,
+    filter: &SelectionFilter,
+    resolve: impl Fn(Entity) -> Entity,
+) {
+    let touched: HashSet<&str> = match action {
+        SelectionAction::Click { clicked, .. } => clicked
+            .iter()
+            .filter(|clicked| !clicked.no_deselect)
+            .map(|clicked| scope_name(clicked.scope.as_ref()))
+            .collect(),
+        _ => HashSet::new(),
+    };
+    for (entity, mut selection, scope) in selections {
+        if resolve(entity) != entity {
+            continue;
+        }
+        let selected = match action {
+            SelectionAction::SelectAll if scopes.mode(scope) == ScopeMode::Multi => true,
+            SelectionAction::DeselectAll if scopes.mode(scope) != ScopeMode::Radio => false,
+            SelectionAction::ClickNothing
+                if scope_name(scope).is_empty() && scopes.mode(scope) != ScopeMode::Radio =>
+            {
+                false
+            }
+            SelectionAction::Invert if scopes.mode(scope) == ScopeMode::Multi => {
+                !selection.selected
+            }
+            SelectionAction::Click {
+                clicked,
+                toggle,
+                mode,
+            } if touched.contains(scope_name(scope)) => {
+                let name = scope_name(scope);
+                let is_clicked = clicked.iter().any(|clicked| clicked.entity == entity);
+                // The topmost entity clicked in this scope, for scopes that only select one.
+                let is_chosen = clicked
+                    .iter()
+                    .filter(|clicked| filter.allows(clicked.entity))
+                    .find(|clicked| scope_name(clicked.scope.as_ref()) == name)
+                    .map(|clicked| clicked.entity)
+                    == Some(entity);
+                let toggle = *toggle;
+                match (scopes.mode(scope), mode) {
+                    (ScopeMode::Multi, _) if toggle && is_clicked => !selection.selected,
+                    // Entities that were not clicked on are deselected, unless a modifier is held.
+                    (ScopeMode::Multi, SelectionMode::Replace) if !toggle => is_clicked,
+                    (ScopeMode::Multi, SelectionMode::Replace | SelectionMode::Add) => {
+                        selection.selected || is_clicked
+                    }
+                    (ScopeMode::Multi, SelectionMode::Subtract) => {
+                        selection.selected && !is_clicked
+                    }
+                    (ScopeMode::Single, _) if toggle && is_chosen => !selection.selected,
+                    (ScopeMode::Single, SelectionMode::Subtract) => {
+                        selection.selected && !is_chosen
+                    }
+                    (ScopeMode::Single, _) => is_chosen,
+                    (ScopeMode::Radio, SelectionMode::Subtract) => selection.selected,
+                    (ScopeMode::Radio, _) => is_chosen,
                 }
             }
+            _ => continue,
+        };
+        if selected && !filter.allows(entity) {
+            continue;
+        }
+        if selection.selected != selected {
+            selection.selected = selected;
         }
     }
 }
+
+/// The selection changes a [SelectionCommands] method queues.
+enum SelectionCommand {
+    Select(Entity),
+    Deselect(Entity),
+    Toggle(Entity),
+    SelectOnly(Entity),
+    SelectAll,
+    Clear,
+    Invert,
+}
+
+impl Command for SelectionCommand {
+    fn write(self, world: &mut World) {
+        let existing = world.remove_resource::<SelectionFilter>();
+        let had_filter = existing.is_some();
+        let mut filter = existing.unwrap_or_else(|| {
+            // Without the plugin's filter, there is no verdict from this frame to reuse.
+            let mut filter = SelectionFilter::default();
+            filter.update(world);
+            filter
+        });
+        let resolve = |world: &World, entity: Entity| {
+            find_root(
+                entity,
+                |entity| world.get::<Parent>(entity).map(Parent::get),
+                |entity| world.get::<SelectionRoot>(entity).is_some(),
+            )
+            .unwrap_or(entity)
+        };
+        let mut click = |world: &World, entity: Entity, toggle: bool, mode: SelectionMode| {
+            let entity = resolve(world, entity);
+            // The command may run long after the filter's last update, so its verdict on the
+            // entity the command targets is brought up to date. The other entities keep this
+            // frame's verdict, which is all that bulk commands go by.
+            filter.update_entity(world, entity);
+            SelectionAction::Click {
+                clicked: vec![ClickedEntity {
+                    entity,
+                    scope: world.get::<SelectionScope>(entity).cloned(),
+                    no_deselect: world.get::<NoDeselect>(entity).is_some()
+                        || !filter.allows(entity),
+                }],
+                toggle,
+                mode,
+            }
+        };
+        let action = match self {
+            SelectionCommand::Select(entity) => click(world, entity, false, SelectionMode::Add),
+            SelectionCommand::Deselect(entity) => {
+                click(world, entity, false, SelectionMode::Subtract)
+            }
+            SelectionCommand::Toggle(entity) => click(world, entity, true, SelectionMode::Replace),
+            SelectionCommand::SelectOnly(entity) => {
+                click(world, entity, false, SelectionMode::Replace)
+            }
+            SelectionCommand::SelectAll => SelectionAction::SelectAll,
+            SelectionCommand::Clear => SelectionAction::DeselectAll,
+            SelectionCommand::Invert => SelectionAction::Invert,
+        };
+        let scopes = world
+            .get_resource::<SelectionScopes>()
+            .cloned()
+            .unwrap_or_default();
+        // Roots are found up front, as the hierarchy can't be read while the selections are
+        // borrowed.
+        let resolved: HashMap<Entity, Entity> = world
+            .query_filtered::<Entity, With<Selection>>()
+            .iter(world)
+            .map(|entity| (entity, resolve(world, entity)))
+            .collect();
+        let mut query = world.query::<(Entity, &mut Selection, Option<&SelectionScope>)>();
+        apply_selection_action(query.iter_mut(world), &action, &scopes, &filter, |entity| {
+            resolved.get(&entity).copied().unwrap_or(entity)
+        });
+        if had_filter {
+            world.insert_resource(filter);
+        }
+    }
+}
+
+/// Changes the selection from code the same way pointers and the [SelectionBindings] do, following
+/// the [ScopeMode] of each [SelectionScope] and sending the same
+/// [SelectionEvent](crate::SelectionEvent)s.
+pub trait SelectionCommands {
+    /// Selects `entity`, like clicking it while holding an `add` binding.
+    fn select(&mut self, entity: Entity);
+    /// Deselects `entity`, like clicking it while holding a `subtract` binding.
+    fn deselect(&mut self, entity: Entity);
+    /// Toggles the selection of `entity`, like clicking it while holding a `toggle` binding.
+    fn toggle_selection(&mut self, entity: Entity);
+    /// Selects `entity` and deselects everything else in its scope, like clicking it.
+    fn select_only(&mut self, entity: Entity);
+    /// Selects everything in [ScopeMode::Multi] scopes, like a `select_all` binding.
+    fn select_all(&mut self);
+    /// Deselects everything outside [ScopeMode::Radio] scopes, like a `deselect_all` binding.
+    fn clear_selection(&mut self);
+    /// Flips the selection of everything in [ScopeMode::Multi] scopes.
+    fn invert_selection(&mut self);
+}
+
+impl<'w, 's> SelectionCommands for Commands<'w, 's> {
+    fn select(&mut self, entity: Entity) {
+        self.add(SelectionCommand::Select(entity));
+    }
+    fn deselect(&mut self, entity: Entity) {
+        self.add(SelectionCommand::Deselect(entity));
+    }
+    fn toggle_selection(&mut self, entity: Entity) {
+        self.add(SelectionCommand::Toggle(entity));
+    }
+    fn select_only(&mut self, entity: Entity) {
+        self.add(SelectionCommand::SelectOnly(entity));
+    }
+    fn select_all(&mut self) {
+        self.add(SelectionCommand::SelectAll);
+    }
+    fn clear_selection(&mut self) {
+        self.add(SelectionCommand::Clear);
+    }
+    fn invert_selection(&mut self) {
+        self.add(SelectionCommand::Invert);
+    }
+}
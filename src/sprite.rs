@@ -0,0 +1,320 @@
+use crate::{
+    backend::{PickingBackend, PickingHits},
+    Hover, Pickable, PickingCamera, PointerHit, Pointers, Selection,
+};
+use bevy::{math::Ray, prelude::*, ui::FocusPolicy};
+
+/// Marks a [Sprite] or [TextureAtlasSprite] to be picked by the [SpriteBackend]. Sprites have no
+/// mesh, so they can't be a [PickableMesh](crate::PickableMesh).
+#[derive(Component, Debug, Copy, Clone, Reflect, Default)]
+#[reflect(Component)]
+pub struct PickableSprite;
+
+/// Makes a sprite pickable. Sprites have no material to swap, so unlike
+/// [PickableBundle](crate::PickableBundle) this does not include highlighting.
+#[derive(Bundle, Default)]
+pub struct PickableSpriteBundle {
+    pub pickable: Pickable,
+    pub pickable_sprite: PickableSprite,
+    pub interaction: Interaction,
+    pub focus_policy: FocusPolicy,
+    pub selection: Selection,
+    pub hover: Hover,
+}
+
+/// Hit-tests the pointers against the rect of every visible [PickableSprite], from every
+/// [PickingCamera].
+pub struct SpriteBackend;
+
+impl PickingBackend for SpriteBackend {
+    fn systems() -> SystemSet {
+        SystemSet::new().with_system(sprite_hits)
+    }
+}
+
+/// The rect a sprite is drawn in, in its local space.
+struct SpriteBounds<'a> {
+    entity: Entity,
+    transform: &'a GlobalTransform,
+    min: Vec2,
+    max: Vec2,
+}
+
+impl<'a> SpriteBounds<'a> {
+    /// The rect of a sprite of `size` around its `anchor`. Flipping mirrors the texture inside
+    /// this rect, so it does not move the bounds.
+    fn new(entity: Entity, transform: &'a GlobalTransform, size: Vec2, anchor: Vec2) -> Self {
+        let min = -(anchor + Vec2::splat(0.5)) * size;
+        Self {
+            entity,
+            transform,
+            min,
+            max: min + size,
+        }
+    }
+
+    /// Where `ray` passes through the sprite's plane inside its rect, and how far along the ray
+    /// that is.
+    fn intersect(&self, ray: &Ray) -> Option<(Vec3, f32)> {
+        let world_to_local = self.transform.compute_matrix().inverse();
+        let origin = world_to_local.transform_point3(ray.origin);
+        let direction = world_to_local.transform_vector3(ray.direction);
+        // Seen edge-on, a sprite has no area to hit.
+        if direction.z.abs() < f32::EPSILON {
+            return None;
+        }
+        let distance = -origin.z / direction.z;
+        if distance < 0.0 {
+            return None;
+        }
+        let local = (origin + direction * distance).truncate();
+        (local.cmpge(self.min).all() && local.cmple(self.max).all())
+            .then(|| (ray.origin + ray.direction * distance, distance))
+    }
+}
+
+/// The size a [Sprite] is drawn at: its `custom_size`, or else the size of its `rect`, or else the
+/// size of its `image`. `None` while the image hasn't loaded.
+fn sprite_size(sprite: &Sprite, image: Option<&Image>) -> Option<Vec2> {
+    sprite
+        .custom_size
+        .or_else(|| sprite.rect.map(|rect| rect.size()))
+        .or_else(|| image.map(|image| image.size()))
+}
+
+/// The size a [TextureAtlasSprite] is drawn at: its `custom_size`, or else the size of its rect in
+/// the `atlas`. `None` while the atlas hasn't loaded.
+fn atlas_sprite_size(sprite: &TextureAtlasSprite, atlas: Option<&TextureAtlas>) -> Option<Vec2> {
+    sprite
+        .custom_size
+        .or_else(|| atlas?.textures.get(sprite.index).map(|rect| rect.size()))
+}
+
+/// Pushes the [PickableSprite]s under each pointer into [PickingHits].
+///
+/// A sprite's size is its `custom_size`, or else the size of its `rect` or atlas rect, or else
+/// the size of its image. Sprites whose image or atlas hasn't loaded yet can't be hit. Hits are
+/// ordered by their distance along the camera's ray, which for a 2D camera is by z: sprites with
+/// a higher z are on top.
+#[allow(clippy::type_complexity)]
+pub fn sprite_hits(
+    pointers: Res<Pointers>,
+    mut hits: ResMut<PickingHits>,
+    images: Res<Assets<Image>>,
+    atlases: Res<Assets<TextureAtlas>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<PickingCamera>>,
+    sprites: Query<
+        (
+            Entity,
+            &Sprite,
+            &Handle<Image>,
+            &GlobalTransform,
+            &ComputedVisibility,
+        ),
+        With<PickableSprite>,
+    >,
+    atlas_sprites: Query<
+        (
+            Entity,
+            &TextureAtlasSprite,
+            &Handle<TextureAtlas>,
+            &GlobalTransform,
+            &ComputedVisibility,
+        ),
+        With<PickableSprite>,
+    >,
+) {
+    let sprite_bounds = sprites
+        .iter()
+        .filter(|(.., visibility)| visibility.is_visible())
+        .filter_map(|(entity, sprite, image, transform, _)| {
+            let size = sprite_size(sprite, images.get(image))?;
+            Some(SpriteBounds::new(
+                entity,
+                transform,
+                size,
+                sprite.anchor.as_vec(),
+            ))
+        });
+    let atlas_sprite_bounds = atlas_sprites
+        .iter()
+        .filter(|(.., visibility)| visibility.is_visible())
+        .filter_map(|(entity, sprite, atlas, transform, _)| {
+            let size = atlas_sprite_size(sprite, atlases.get(atlas))?;
+            Some(SpriteBounds::new(
+                entity,
+                transform,
+                size,
+                sprite.anchor.as_vec(),
+            ))
+        });
+    let bounds: Vec<_> = sprite_bounds.chain(atlas_sprite_bounds).collect();
+    if bounds.is_empty() {
+        return;
+    }
+
+    for (camera, camera_transform) in cameras.iter() {
+        if !camera.is_active {
+            continue;
+        }
+        for (pointer, state) in pointers.iter() {
+            let ray = match state
+                .viewport_position(camera)
+                .and_then(|position| camera.viewport_to_world(camera_transform, position))
+            {
+                Some(ray) => ray,
+                None => continue,
+            };
+            for sprite in bounds.iter() {
+                if let Some((position, distance)) = sprite.intersect(&ray) {
+                    let hit = PointerHit {
+                        pointer,
+                        entity: sprite.entity,
+                        position,
+                        normal: sprite.transform.back(),
+                        distance,
+                        triangle: None,
+                    };
+                    hits.push(Some(camera.target.clone()), camera.priority, distance, hit);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{math::Rect, sprite::Anchor};
+
+    const SIZE: Vec2 = Vec2::new(20.0, 10.0);
+
+    fn bounds<'a>(transform: &'a GlobalTransform, anchor: &Anchor) -> SpriteBounds<'a> {
+        SpriteBounds::new(Entity::from_raw(0), transform, SIZE, anchor.as_vec())
+    }
+
+    /// A ray straight down the z axis through `(x, y)`, as a 2D camera casts them.
+    fn ray(x: f32, y: f32) -> Ray {
+        Ray {
+            origin: Vec3::new(x, y, 999.0),
+            direction: Vec3::NEG_Z,
+        }
+    }
+
+    #[test]
+    fn anchors() {
+        let transform = GlobalTransform::default();
+        for (anchor, min) in [
+            (Anchor::Center, Vec2::new(-10.0, -5.0)),
+            (Anchor::BottomLeft, Vec2::ZERO),
+            (Anchor::TopRight, Vec2::new(-20.0, -10.0)),
+            (Anchor::CenterLeft, Vec2::new(0.0, -5.0)),
+            (Anchor::Custom(Vec2::new(0.25, -0.5)), Vec2::new(-15.0, 0.0)),
+        ] {
+            let bounds = bounds(&transform, &anchor);
+            assert_eq!((bounds.min, bounds.max), (min, min + SIZE), "{anchor:?}");
+        }
+    }
+
+    #[test]
+    fn anchored_sprites_are_hit_where_they_are_drawn() {
+        let transform = GlobalTransform::from_xyz(100.0, 100.0, 0.0);
+        let bottom_left = bounds(&transform, &Anchor::BottomLeft);
+        assert!(bottom_left.intersect(&ray(115.0, 105.0)).is_some());
+        assert!(bottom_left.intersect(&ray(95.0, 95.0)).is_none());
+        let custom = bounds(&transform, &Anchor::Custom(Vec2::new(0.25, -0.5)));
+        assert!(custom.intersect(&ray(86.0, 109.0)).is_some());
+        assert!(custom.intersect(&ray(106.0, 105.0)).is_none());
+    }
+
+    #[test]
+    fn edges_are_inside() {
+        let transform = GlobalTransform::default();
+        let bounds = bounds(&transform, &Anchor::BottomLeft);
+        for (x, y) in [(0.0, 0.0), (20.0, 10.0), (20.0, 5.0), (10.0, 0.0)] {
+            assert!(bounds.intersect(&ray(x, y)).is_some(), "{x}, {y}");
+        }
+        for (x, y) in [(20.01, 5.0), (-0.01, 5.0), (10.0, 10.01), (10.0, -0.01)] {
+            assert!(bounds.intersect(&ray(x, y)).is_none(), "{x}, {y}");
+        }
+    }
+
+    #[test]
+    fn higher_sprites_are_nearer() {
+        let (back, front) = (
+            GlobalTransform::from_xyz(0.0, 0.0, 0.0),
+            GlobalTransform::from_xyz(5.0, 0.0, 1.0),
+        );
+        let (back, front) = (
+            bounds(&back, &Anchor::Center),
+            bounds(&front, &Anchor::Center),
+        );
+        let (back_hit, back_distance) = back.intersect(&ray(0.0, 0.0)).unwrap();
+        let (front_hit, front_distance) = front.intersect(&ray(0.0, 0.0)).unwrap();
+        assert!(front_distance < back_distance);
+        assert_eq!(back_hit, Vec3::ZERO);
+        assert_eq!(front_hit, Vec3::Z);
+    }
+
+    #[test]
+    fn sprites_seen_edge_on_or_from_behind_are_missed() {
+        let transform = GlobalTransform::default();
+        let bounds = bounds(&transform, &Anchor::Center);
+        let edge_on = Ray {
+            origin: Vec3::new(-50.0, 0.0, 0.0),
+            direction: Vec3::X,
+        };
+        assert!(bounds.intersect(&edge_on).is_none());
+        let behind = Ray {
+            origin: Vec3::new(0.0, 0.0, -1.0),
+            direction: Vec3::NEG_Z,
+        };
+        assert!(bounds.intersect(&behind).is_none());
+    }
+
+    #[test]
+    fn sprite_sizes() {
+        let image = Image::default();
+        let rect = Rect::new(0.0, 0.0, 8.0, 4.0);
+        let sprite = Sprite {
+            custom_size: Some(SIZE),
+            rect: Some(rect),
+            ..default()
+        };
+        assert_eq!(sprite_size(&sprite, Some(&image)), Some(SIZE));
+        let sprite = Sprite {
+            rect: Some(rect),
+            ..default()
+        };
+        assert_eq!(sprite_size(&sprite, Some(&image)), Some(rect.size()));
+        assert_eq!(
+            sprite_size(&Sprite::default(), Some(&image)),
+            Some(image.size())
+        );
+        assert_eq!(sprite_size(&Sprite::default(), None), None);
+    }
+
+    #[test]
+    fn atlas_sprite_sizes() {
+        let mut atlas = TextureAtlas::new_empty(Handle::default(), Vec2::new(16.0, 8.0));
+        atlas.add_texture(Rect::new(0.0, 0.0, 8.0, 8.0));
+        let index = atlas.add_texture(Rect::new(8.0, 0.0, 16.0, 4.0));
+        let sprite = TextureAtlasSprite { index, ..default() };
+        assert_eq!(
+            atlas_sprite_size(&sprite, Some(&atlas)),
+            Some(Vec2::new(8.0, 4.0))
+        );
+        assert_eq!(atlas_sprite_size(&sprite, None), None);
+        let custom = TextureAtlasSprite {
+            index,
+            custom_size: Some(SIZE),
+            ..default()
+        };
+        assert_eq!(atlas_sprite_size(&custom, Some(&atlas)), Some(SIZE));
+        let missing = TextureAtlasSprite {
+            index: 2,
+            ..default()
+        };
+        assert_eq!(atlas_sprite_size(&missing, Some(&atlas)), None);
+    }
+}